@@ -1,27 +1,33 @@
-use crayon::Color;
+use crayon::{Color, ColorSpace, Gradient, Rgb, StyledText};
 
-fn main() -> () {
-    let text = vec![
+fn main() {
+    let text: StyledText = [
         "c".red().bg_white().bold(),
         "r".green().bg_white().bold(),
         "a".yellow().bg_white().bold(),
         "y".blue().bg_white().bold(),
         "o".magenta().bg_white().bold(),
         "n".cyan().bg_white().bold(),
-    ];
-    println!(
-        "{0}   {0}",
-        text.iter()
-            .fold(String::new(), |acc, x| { acc + &x.to_string() })
-    );
+    ]
+    .into_iter()
+    .collect();
+    println!("{0}   {0}", text);
     crayon::disable_color();
+    println!("{0}   {0}", text);
+    crayon::enable_color();
+    println!();
+    let stops = [
+        Rgb::new(255, 0, 0),
+        Rgb::new(255, 255, 0),
+        Rgb::new(0, 0, 255),
+    ];
+    println!("{}", "crayon crayon crayon".gradient(&stops).bold());
     println!(
-        "{0}   {0}",
-        text.iter()
-            .fold(String::new(), |acc, x| { acc + &x.to_string() })
+        "{}",
+        " ".repeat(20)
+            .gradient(Gradient::new(&stops).background().space(ColorSpace::Lab))
     );
-    crayon::enable_color();
-    println!("");
+    println!();
     print!("{} ", "1".black());
     print!("{} ", "2".red());
     print!("{} ", "3".green());
@@ -30,7 +36,16 @@ fn main() -> () {
     print!("{} ", "6".magenta());
     print!("{} ", "7".cyan());
     print!("{} ", "8".white());
-    println!("");
+    println!();
+    print!("{} ", "1".bright_black());
+    print!("{} ", "2".bright_red());
+    print!("{} ", "3".bright_green());
+    print!("{} ", "4".bright_yellow());
+    print!("{} ", "5".bright_blue());
+    print!("{} ", "6".bright_magenta());
+    print!("{} ", "7".bright_cyan());
+    print!("{} ", "8".bright_white());
+    println!();
     print!("{} ", "1".bg_black());
     print!("{} ", "2".bg_red());
     print!("{} ", "3".bg_green());
@@ -39,7 +54,16 @@ fn main() -> () {
     print!("{} ", "6".bg_magenta());
     print!("{} ", "7".bg_cyan());
     print!("{} ", "8".bg_white());
-    println!("");
+    println!();
+    print!("{} ", "1".bg_bright_black());
+    print!("{} ", "2".bg_bright_red());
+    print!("{} ", "3".bg_bright_green());
+    print!("{} ", "4".bg_bright_yellow());
+    print!("{} ", "5".bg_bright_blue());
+    print!("{} ", "6".bg_bright_magenta());
+    print!("{} ", "7".bg_bright_cyan());
+    print!("{} ", "8".bg_bright_white());
+    println!();
     print!("{} ", "1".black().underline());
     print!("{} ", "2".red().underline());
     print!("{} ", "3".green().underline());
@@ -48,7 +72,7 @@ fn main() -> () {
     print!("{} ", "6".magenta().underline());
     print!("{} ", "7".cyan().underline());
     print!("{} ", "8".white().underline());
-    println!("");
+    println!();
     print!("{} ", "1".black().bold());
     print!("{} ", "2".red().bold());
     print!("{} ", "3".green().bold());
@@ -57,7 +81,7 @@ fn main() -> () {
     print!("{} ", "6".magenta().bold());
     print!("{} ", "7".cyan().bold());
     print!("{} ", "8".white().bold());
-    println!("");
+    println!();
     print!("{} ", "1".black().reversed());
     print!("{} ", "2".red().reversed());
     print!("{} ", "3".green().reversed());
@@ -66,7 +90,7 @@ fn main() -> () {
     print!("{} ", "6".magenta().reversed());
     print!("{} ", "7".cyan().reversed());
     print!("{} ", "8".white().reversed());
-    println!("");
+    println!();
     print!("{} ", "1".black().bold().underline());
     print!("{} ", "2".red().bold().underline());
     print!("{} ", "3".green().bold().underline());
@@ -75,8 +99,8 @@ fn main() -> () {
     print!("{} ", "6".magenta().bold().underline());
     print!("{} ", "7".cyan().bold().underline());
     print!("{} ", "8".white().bold().underline());
-    println!("");
-    println!("");
+    println!();
+    println!();
 
     let mut i = 0;
     for r in 0..6 {
@@ -91,19 +115,19 @@ fn main() -> () {
         let p = 8 + (i*10);
         print!("{} ", (i + 232).to_string().rgb(p, p, p));
     }
-    println!("");
-    println!("");
+    println!();
+    println!();
 
     for i in 0..16 {
         print!("{} ", i.to_string().wheel(i));
     }
-    println!("");
+    println!();
 
     for i in 16..=255 {
         print!("{} ", i.to_string().wheel(i));
     }
-    println!("");
-    println!("");
+    println!();
+    println!();
 
     let mut i = 0;
     for r in 0..6 {
@@ -119,18 +143,18 @@ fn main() -> () {
 
         print!("{}", format!("{} ", i+232).bg_rgb(p, p, p));
     }
-    println!("");
+    println!();
 
-    println!("");
+    println!();
     for i in 0..16 {
         print!("{}", format!("{} ", i).bg_wheel(i));
     }
-    println!("");
-    println!("");
+    println!();
+    println!();
 
     for i in 16..=255 {
         print!("{}", format!("{} ", i).bg_wheel(i));
     }
-    println!("");
+    println!();
 
 }
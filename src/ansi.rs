@@ -0,0 +1,292 @@
+//! Recognizes escape sequences embedded in text.
+//!
+//! Follows the ECMA-48 grammar for CSI sequences, string sequences (OSC, DCS,
+//! SOS, PM and APC) terminated by BEL or ST, and two-character escapes. The
+//! 8-bit C1 forms of these introducers are recognized in their UTF-8 encoding.
+
+/// Where the scanner is within the text.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    /// Reading plain text.
+    Ground,
+    /// Holding a `0xC2` byte that may start a C1 control.
+    C1,
+    /// After `ESC`.
+    Escape,
+    /// After `ESC` and one or more intermediate bytes.
+    Intermediate,
+    /// Within a control sequence.
+    Csi,
+    /// Within a string sequence.
+    Str,
+    /// After `ESC` within a string sequence.
+    StrEscape,
+    /// After `0xC2` within a string sequence.
+    StrC1,
+}
+
+/// What a byte fed to the [Scanner] turned out to be.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Action {
+    /// The byte is plain text.
+    Text,
+    /// The held byte and this byte are both plain text.
+    HeldText,
+    /// The byte is held until the next byte decides what it is.
+    Hold,
+    /// The byte is part of an escape sequence that continues.
+    Skip,
+    /// The byte completes an escape sequence.
+    End,
+    /// The escape sequence ended before this byte, which must be fed again.
+    Abort,
+    /// The held byte is plain text on its own, and this byte must be fed
+    /// again.
+    Release,
+}
+
+/// A byte-at-a-time state machine separating escape sequences from text.
+///
+/// The state is kept between calls, so text can be scanned in chunks.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct Scanner {
+    state: State,
+}
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+impl Scanner {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: State::Ground,
+        }
+    }
+
+    pub(crate) fn feed(&mut self, byte: u8) -> Action {
+        let (state, action) = match (self.state, byte) {
+            (State::Ground, ESC) => (State::Escape, Action::Skip),
+            (State::Ground, 0xc2) => (State::C1, Action::Hold),
+            (State::Ground, _) => (State::Ground, Action::Text),
+            // CSI
+            (State::C1, 0x9b) => (State::Csi, Action::Skip),
+            // OSC, DCS, SOS, PM and APC
+            (State::C1, 0x9d | 0x90 | 0x98 | 0x9e | 0x9f) => (State::Str, Action::Skip),
+            (State::C1, 0x80..=0x9f) => (State::Ground, Action::End),
+            (State::C1, 0xa0..=0xbf) => (State::Ground, Action::HeldText),
+            (State::C1, _) => (State::Ground, Action::Release),
+            (State::Escape, b'[') => (State::Csi, Action::Skip),
+            (State::Escape, b']' | b'P' | b'X' | b'^' | b'_') => (State::Str, Action::Skip),
+            (State::Escape, 0x20..=0x2f) => (State::Intermediate, Action::Skip),
+            (State::Escape, 0x30..=0x7e) => (State::Ground, Action::End),
+            (State::Escape, _) => (State::Ground, Action::Abort),
+            (State::Intermediate, 0x20..=0x2f) => (State::Intermediate, Action::Skip),
+            (State::Intermediate, 0x30..=0x7e) => (State::Ground, Action::End),
+            (State::Intermediate, _) => (State::Ground, Action::Abort),
+            (State::Csi, 0x20..=0x3f) => (State::Csi, Action::Skip),
+            (State::Csi, 0x40..=0x7e) => (State::Ground, Action::End),
+            (State::Csi, _) => (State::Ground, Action::Abort),
+            (State::Str, BEL) => (State::Ground, Action::End),
+            (State::Str, ESC) => (State::StrEscape, Action::Skip),
+            (State::Str, 0xc2) => (State::StrC1, Action::Skip),
+            (State::Str, _) => (State::Str, Action::Skip),
+            (State::StrEscape, b'\\') => (State::Ground, Action::End),
+            // an escape other than ST interrupts the string and starts anew
+            (State::StrEscape, _) => {
+                self.state = State::Escape;
+                return self.feed(byte);
+            }
+            (State::StrC1, 0x9c) => (State::Ground, Action::End),
+            (State::StrC1, _) => (State::Str, Action::Skip),
+        };
+        self.state = state;
+        action
+    }
+}
+
+/// A piece of text split apart by [Tokens].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Token<'a> {
+    /// Plain text.
+    Text(&'a str),
+    /// A control sequence with its parameter and intermediate bytes.
+    Csi { params: &'a str, action: char },
+    /// An operating system command with its payload.
+    Osc(&'a str),
+    /// Any other escape sequence, including incomplete ones.
+    Other(&'a str),
+}
+
+/// Iterates over the text and escape sequences of a string.
+#[derive(Debug, Clone)]
+pub(crate) struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn new(s: &'a str) -> Self {
+        Self { rest: s }
+    }
+
+    /// References the part of the string not yet iterated over.
+    pub(crate) fn as_str(&self) -> &'a str {
+        self.rest
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let bytes = self.rest.as_bytes();
+        let mut scanner = Scanner::new();
+        let mut len = bytes.len();
+        for (i, &byte) in bytes.iter().enumerate() {
+            let mut action = scanner.feed(byte);
+            if action == Action::Release {
+                action = scanner.feed(byte);
+            }
+            match action {
+                Action::Text | Action::HeldText | Action::Hold => continue,
+                action => {
+                    // a C1 control starts at the byte held before it
+                    let start = if byte == ESC { i } else { i - 1 };
+                    if start > 0 {
+                        len = start;
+                        break;
+                    }
+                    len = match action {
+                        Action::End => i + 1,
+                        _ => sequence_len(&mut scanner, bytes, i + 1),
+                    };
+                    let (seq, rest) = self.rest.split_at(len);
+                    self.rest = rest;
+                    return Some(classify(seq));
+                }
+            }
+        }
+        let (text, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Some(Token::Text(text))
+    }
+}
+
+/// Feeds `bytes` from `from` on into the `scanner` and finds where the escape
+/// sequence it is in ends.
+fn sequence_len(scanner: &mut Scanner, bytes: &[u8], from: usize) -> usize {
+    for (i, &byte) in bytes.iter().enumerate().skip(from) {
+        match scanner.feed(byte) {
+            Action::End => return i + 1,
+            Action::Abort => return i,
+            _ => {}
+        }
+    }
+    bytes.len()
+}
+
+/// Identifies a complete escape sequence by its introducer.
+fn classify(seq: &str) -> Token<'_> {
+    let mut chars = seq.chars();
+    let mut introducer = chars.next();
+    if introducer == Some('\u{1b}') {
+        introducer = chars.next();
+    }
+    match (introducer, chars.as_str()) {
+        (Some('[' | '\u{9b}'), rest) => match rest.chars().last() {
+            Some(action) if ('\u{40}'..='\u{7e}').contains(&action) => Token::Csi {
+                params: &rest[..rest.len() - 1],
+                action,
+            },
+            _ => Token::Other(seq),
+        },
+        (Some(']' | '\u{9d}'), rest) => {
+            // the payload ends at the first terminator
+            let end = rest
+                .find(['\u{07}', '\u{1b}', '\u{9c}'])
+                .unwrap_or(rest.len());
+            Token::Osc(&rest[..end])
+        }
+        _ => Token::Other(seq),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_tokens() {
+        let tokens: Vec<Token> =
+            Tokens::new("a\u{1b}[1;31mb\u{1b}]8;;url\u{1b}\\c\u{1b}(Bd\u{9b}0m").collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("a"),
+                Token::Csi {
+                    params: "1;31",
+                    action: 'm'
+                },
+                Token::Text("b"),
+                Token::Osc("8;;url"),
+                Token::Text("c"),
+                Token::Other("\u{1b}(B"),
+                Token::Text("d"),
+                Token::Csi {
+                    params: "0",
+                    action: 'm'
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_tokens_edge_cases() {
+        // non-breaking space starts with the same byte as a C1 control
+        assert_eq!(
+            Tokens::new("a\u{a0}b").collect::<Vec<Token>>(),
+            vec![Token::Text("a\u{a0}b")]
+        );
+        // BEL terminates an OSC
+        assert_eq!(
+            Tokens::new("\u{1b}]0;title\u{7}x").collect::<Vec<Token>>(),
+            vec![Token::Osc("0;title"), Token::Text("x")]
+        );
+        // an unterminated sequence runs to the end
+        assert_eq!(
+            Tokens::new("x\u{1b}[31").collect::<Vec<Token>>(),
+            vec![Token::Text("x"), Token::Other("\u{1b}[31")]
+        );
+        // an invalid byte aborts the sequence and is kept as text
+        assert_eq!(
+            Tokens::new("\u{1b}[3\u{e9}").collect::<Vec<Token>>(),
+            vec![Token::Other("\u{1b}[3"), Token::Text("\u{e9}")]
+        );
+        // a lone C1 control
+        assert_eq!(
+            Tokens::new("\u{9c}z").collect::<Vec<Token>>(),
+            vec![Token::Other("\u{9c}"), Token::Text("z")]
+        );
+        assert_eq!(Tokens::new("").next(), None);
+    }
+
+    #[test]
+    fn ut_scanner_chunks() {
+        let mut scanner = Scanner::new();
+        let actions: Vec<Action> = "\u{1b}[3".bytes().map(|b| scanner.feed(b)).collect();
+        assert_eq!(actions, vec![Action::Skip, Action::Skip, Action::Skip]);
+        assert_eq!(scanner.feed(b'1'), Action::Skip);
+        assert_eq!(scanner.feed(b'm'), Action::End);
+        assert_eq!(scanner.feed(0xc2), Action::Hold);
+        assert_eq!(scanner.feed(0xa0), Action::HeldText);
+        assert_eq!(scanner.feed(b'x'), Action::Text);
+        // a byte that cannot follow the held one starts over
+        assert_eq!(scanner.feed(0xc2), Action::Hold);
+        assert_eq!(scanner.feed(0xc2), Action::Release);
+        assert_eq!(scanner.feed(0xc2), Action::Hold);
+        assert_eq!(scanner.feed(ESC), Action::Release);
+        assert_eq!(scanner.feed(ESC), Action::Skip);
+    }
+}
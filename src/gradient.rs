@@ -0,0 +1,227 @@
+use super::lab::Lab;
+use super::palette::{Bg, Fg};
+use super::{parse, width, Code, ColoredStr, Rgb, StyledText};
+
+/// The color space a [Gradient] blends its stops in.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub enum ColorSpace {
+    /// Blends the red, green and blue components separately.
+    #[default]
+    Rgb,
+    /// Blends in CIELAB so the colors change at an even pace to the eye and
+    /// the middle of a ramp does not turn muddy.
+    Lab,
+}
+
+/// A color ramp through evenly spaced stops that colors text one grapheme
+/// cluster at a time.
+///
+/// An array or slice of [Rgb] stops converts into a foreground gradient
+/// blended in RGB.
+///
+/// ```
+/// use crayon::{Color, ColorSpace, Gradient, Rgb};
+///
+/// const SUNSET: [Rgb; 3] = [
+///     Rgb::new(255, 94, 77),
+///     Rgb::new(255, 166, 0),
+///     Rgb::new(102, 51, 153),
+/// ];
+/// const BAR: Gradient = Gradient::new(&SUNSET).background().space(ColorSpace::Lab);
+///
+/// println!("{}", "crayon".gradient(&SUNSET).bold());
+/// println!("{}", " ".repeat(20).gradient(BAR));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Gradient<'a> {
+    stops: &'a [Rgb],
+    pub(crate) background: bool,
+    space: ColorSpace,
+}
+
+impl<'a> Gradient<'a> {
+    /// Ramps from the first to the last of `stops`, which are spread evenly
+    /// over the text.
+    pub const fn new(stops: &'a [Rgb]) -> Self {
+        Self {
+            stops,
+            background: false,
+            space: ColorSpace::Rgb,
+        }
+    }
+
+    /// Colors the background instead of the foreground.
+    pub const fn background(mut self) -> Self {
+        self.background = true;
+        self
+    }
+
+    /// Blends the stops in `space`.
+    pub const fn space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Picks the color at `t` from 0 at the first stop to 1 at the last one,
+    /// or `None` without any stops.
+    ///
+    /// ```
+    /// use crayon::{Gradient, Rgb};
+    ///
+    /// let stops = [Rgb::new(0, 0, 0), Rgb::new(255, 255, 255)];
+    /// let ramp = Gradient::new(&stops);
+    /// assert_eq!(ramp.at(0.5), Some(Rgb::new(128, 128, 128)));
+    /// ```
+    pub fn at(&self, t: f64) -> Option<Rgb> {
+        let first = *self.stops.first()?;
+        if self.stops.len() == 1 {
+            return Some(first);
+        }
+        let position = t.clamp(0.0, 1.0) * (self.stops.len() - 1) as f64;
+        let i = (position as usize).min(self.stops.len() - 2);
+        let (from, to, weight) = (self.stops[i], self.stops[i + 1], position - i as f64);
+        Some(match self.space {
+            ColorSpace::Rgb => from.mix(to, weight),
+            ColorSpace::Lab => {
+                let (from, to) = (
+                    Lab::from_rgb(from.r, from.g, from.b),
+                    Lab::from_rgb(to.r, to.g, to.b),
+                );
+                let lerp = |a: f64, b: f64| a + (b - a) * weight;
+                let (r, g, b) = Lab {
+                    l: lerp(from.l, to.l),
+                    a: lerp(from.a, to.a),
+                    b: lerp(from.b, to.b),
+                }
+                .to_rgb();
+                Rgb::new(r, g, b)
+            }
+        })
+    }
+
+    /// Colors each grapheme cluster of `text` by its position, as an outer
+    /// color that spans setting their own keep.
+    ///
+    /// Escape sequences embedded in the text are parsed first, so they take up
+    /// no steps of the ramp and the colors they set are kept as well.
+    pub(crate) fn apply(&self, text: &StyledText) -> StyledText {
+        let text = parse::parse_nested(text);
+        let count: usize = text
+            .spans()
+            .map(|span| width::graphemes(span.data).count())
+            .sum();
+        let mut painted = StyledText::new();
+        let mut i = 0;
+        for span in text.spans() {
+            for grapheme in width::graphemes(span.data) {
+                let t = match count {
+                    0 | 1 => 0.0,
+                    _ => i as f64 / (count - 1) as f64,
+                };
+                let mut outer = Code::new();
+                if let Some(c) = self.at(t) {
+                    match self.background {
+                        true => outer.bg = Some(Bg::Rgb(c.r, c.g, c.b)),
+                        false => outer.fg = Some(Fg::Rgb(c.r, c.g, c.b)),
+                    }
+                }
+                painted.push(ColoredStr {
+                    data: grapheme,
+                    code: outer.overlay(&span.code),
+                    ..span
+                });
+                i += 1;
+            }
+        }
+        painted
+    }
+}
+
+impl<'a> From<&'a [Rgb]> for Gradient<'a> {
+    fn from(stops: &'a [Rgb]) -> Self {
+        Self::new(stops)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [Rgb; N]> for Gradient<'a> {
+    fn from(stops: &'a [Rgb; N]) -> Self {
+        Self::new(stops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        override_color, override_color_level, with_color, with_color_level, Color, ColorLevel,
+    };
+
+    const RED: Rgb = Rgb::new(255, 0, 0);
+    const GREEN: Rgb = Rgb::new(0, 255, 0);
+    const BLUE: Rgb = Rgb::new(0, 0, 255);
+
+    #[test]
+    fn ut_gradient_at() {
+        let ramp = Gradient::new(&[RED, GREEN, BLUE]);
+        assert_eq!(ramp.at(0.0), Some(RED));
+        assert_eq!(ramp.at(0.25), Some(Rgb::new(128, 128, 0)));
+        assert_eq!(ramp.at(0.5), Some(GREEN));
+        assert_eq!(ramp.at(1.0), Some(BLUE));
+        assert_eq!(ramp.at(7.0), Some(BLUE));
+        assert_eq!(Gradient::new(&[RED]).at(0.7), Some(RED));
+        assert_eq!(Gradient::new(&[]).at(0.5), None);
+        // the perceptual middle of black to white is lighter than the RGB one
+        let grey = Gradient::new(&[Rgb::new(0, 0, 0), Rgb::new(255, 255, 255)])
+            .space(ColorSpace::Lab)
+            .at(0.5);
+        assert_eq!(grey, Some(Rgb::new(119, 119, 119)));
+        let lab = Gradient::new(&[RED, BLUE]).space(ColorSpace::Lab);
+        assert_eq!(lab.at(0.0), Some(RED));
+        assert_eq!(lab.at(1.0), Some(BLUE));
+    }
+
+    #[test]
+    fn ut_gradient_text() {
+        assert_eq!(
+            "abc".gradient(&[RED, BLUE]),
+            StyledText::from("a".rgb(255, 0, 0)) + "b".rgb(128, 0, 128) + "c".rgb(0, 0, 255)
+        );
+        // per grapheme cluster rather than per char
+        let text = "e\u{301}\u{1f1fa}\u{1f1f8}".gradient(Gradient::new(&[RED, BLUE]).background());
+        assert_eq!(
+            text.spans().collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("e\u{301}").bg_rgb(255, 0, 0),
+                ColoredStr::new("\u{1f1fa}\u{1f1f8}").bg_rgb(0, 0, 255),
+            ]
+        );
+        // replaces the color of a single code but keeps its other attributes
+        assert_eq!(
+            "ab".green().bold().gradient(&[RED, BLUE]),
+            "a".rgb(255, 0, 0).bold() + "b".rgb(0, 0, 255).bold()
+        );
+        // spans of styled text keep the colors they set themselves
+        let text = ("a".bold() + "b".green()).gradient(&[RED, BLUE]);
+        assert_eq!(text, "a".rgb(255, 0, 0).bold() + "b".green());
+        // as do colors already formatted into the text
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        let text = format!("a{}b", "x".red()).gradient(&[RED, BLUE]);
+        assert_eq!(text, "a".rgb(255, 0, 0) + "x".red() + "b".rgb(0, 0, 255));
+        assert_eq!("".gradient(&[RED, BLUE]), StyledText::new());
+        assert_eq!("ab".gradient(&[]), StyledText::from("ab"));
+    }
+
+    #[test]
+    fn ut_gradient_downgrade() {
+        let text = "abc".gradient(&[RED, BLUE]);
+        with_color(true, || {
+            with_color_level(ColorLevel::Ansi16, || {
+                assert_eq!(
+                    text.to_string(),
+                    "\u{1b}[91ma\u{1b}[35mb\u{1b}[34mc\u{1b}[39m"
+                );
+            })
+        });
+    }
+}
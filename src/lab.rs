@@ -0,0 +1,114 @@
+//! Converts sRGB colors to and from the CIELAB color space, where the
+//! distance between two colors follows how different they look.
+
+/// A color in CIELAB under the D65 white point.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct Lab {
+    pub(crate) l: f64,
+    pub(crate) a: f64,
+    pub(crate) b: f64,
+}
+
+/// Undoes the sRGB transfer function for a component.
+fn linearize(c: u8) -> f64 {
+    let c = c as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Applies the sRGB transfer function to a linear component.
+fn encode(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+// reference white of D65
+const WHITE: (f64, f64, f64) = (0.95047, 1.0, 1.08883);
+const EPSILON: f64 = 216.0 / 24389.0;
+const KAPPA: f64 = 24389.0 / 27.0;
+
+fn f(t: f64) -> f64 {
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+impl Lab {
+    pub(crate) fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        let (r, g, b) = (linearize(r), linearize(g), linearize(b));
+        let x = 0.4124564 * r + 0.3575761 * g + 0.1804375 * b;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = 0.0193339 * r + 0.1191920 * g + 0.9503041 * b;
+        let (fx, fy, fz) = (f(x / WHITE.0), f(y / WHITE.1), f(z / WHITE.2));
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+
+    /// Converts back into sRGB, clamping colors outside of its gamut.
+    pub(crate) fn to_rgb(self) -> (u8, u8, u8) {
+        let fy = (self.l + 16.0) / 116.0;
+        let (fx, fz) = (fy + self.a / 500.0, fy - self.b / 200.0);
+        let f_inv = |t: f64| match t.powi(3) {
+            cube if cube > EPSILON => cube,
+            _ => (116.0 * t - 16.0) / KAPPA,
+        };
+        let x = f_inv(fx) * WHITE.0;
+        let y = f_inv(fy) * WHITE.1;
+        let z = f_inv(fz) * WHITE.2;
+        (
+            encode(3.2404542 * x - 1.5371385 * y - 0.4985314 * z),
+            encode(-0.9692660 * x + 1.8760108 * y + 0.0415560 * z),
+            encode(0.0556434 * x - 0.2040259 * y + 1.0572252 * z),
+        )
+    }
+
+    /// Measures the squared CIE76 color difference (ΔE) to `other`.
+    pub(crate) fn distance(&self, other: &Self) -> f64 {
+        (self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_lab() {
+        let white = Lab::from_rgb(255, 255, 255);
+        assert!((white.l - 100.0).abs() < 0.01);
+        assert!(white.a.abs() < 0.01 && white.b.abs() < 0.01);
+        assert_eq!(Lab::from_rgb(0, 0, 0).l, 0.0);
+        // reference values for pure red
+        let red = Lab::from_rgb(255, 0, 0);
+        assert!((red.l - 53.24).abs() < 0.01);
+        assert!((red.a - 80.09).abs() < 0.01);
+        assert!((red.b - 67.20).abs() < 0.01);
+        assert_eq!(red.distance(&red), 0.0);
+        assert!(red.distance(&Lab::from_rgb(250, 0, 0)) < red.distance(&Lab::from_rgb(0, 0, 255)));
+        for (r, g, b) in [
+            (255, 0, 0),
+            (0, 0, 0),
+            (255, 255, 255),
+            (102, 51, 153),
+            (1, 2, 3),
+        ] {
+            assert_eq!(Lab::from_rgb(r, g, b).to_rgb(), (r, g, b));
+        }
+        // out of gamut colors are clamped
+        let bright = Lab { l: 150.0, ..red };
+        assert_eq!(bright.to_rgb().0, 255);
+    }
+}
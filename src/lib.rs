@@ -1,46 +1,405 @@
-use std::fmt::Display;
-use std::sync::atomic::{AtomicBool, Ordering};
-
-/// Global variable indicating whether the [Display] trait will format the ANSI
-/// codes to the [String] content.
-static IS_COLOR: AtomicBool = AtomicBool::new(true);
+use std::cell::{Cell, RefCell};
+use std::fmt::{Display, Write};
+use std::io::IsTerminal;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+mod ansi;
+mod gradient;
+mod lab;
+mod parse;
+mod strip;
+mod swatch;
+mod text;
+mod width;
+mod wrap;
+
+pub use gradient::{ColorSpace, Gradient};
+pub use parse::parse_ansi;
+pub use strip::{strip_ansi, StripAnsi};
+pub use swatch::{ParseColorError, Rgb, Swatch};
+pub use text::StyledText;
+pub use wrap::{wrap, WrapOptions};
+
+/// Global variables indicating whether the [Display] trait will format the ANSI
+/// codes to the [String] content, indexed by [Stream].
+static IS_COLOR: [AtomicU8; 2] = [AtomicU8::new(color::AUTO), AtomicU8::new(color::AUTO)];
 
 mod color {
-    pub const ON: bool = true;
-    pub const OFF: bool = false;
+    pub const OFF: u8 = 0;
+    pub const ON: u8 = 1;
+    pub const AUTO: u8 = 2;
 }
 
 /// Enables the [Color] trait to store ANSI escape sequences into a [ColoredString].
-pub fn enable_color() -> () {
-    IS_COLOR.store(color::ON, Ordering::SeqCst)
+///
+/// Applies to every [Stream].
+pub fn enable_color() {
+    Stream::Stdout.enable_color();
+    Stream::Stderr.enable_color();
 }
 
 /// Gatekeeps the [Color] trait from storing ANSI escape sequences into a [ColoredString].
-pub fn disable_color() -> () {
-    IS_COLOR.store(color::OFF, Ordering::SeqCst)
+///
+/// Applies to every [Stream].
+pub fn disable_color() {
+    Stream::Stdout.disable_color();
+    Stream::Stderr.disable_color();
+}
+
+/// Lets the environment decide whether the [Color] trait stores ANSI escape
+/// sequences into a [ColoredString] (default).
+///
+/// Colors are written only when the stream is a terminal, unless overridden by
+/// the `NO_COLOR`, `CLICOLOR` or `CLICOLOR_FORCE` environment variables.
+/// Applies to every [Stream].
+pub fn auto_color() {
+    Stream::Stdout.auto_color();
+    Stream::Stderr.auto_color();
+}
+
+/// An output stream with its own policy for writing color escape sequences.
+///
+/// Formatting a [ColoredString] with [Display] follows the policy of
+/// [Stream::Stdout]; use [ColoredString::for_stream] to follow another one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    /// Always writes color escape sequences for this stream.
+    pub fn enable_color(self) {
+        IS_COLOR[self as usize].store(color::ON, Ordering::SeqCst)
+    }
+
+    /// Never writes color escape sequences for this stream.
+    pub fn disable_color(self) {
+        IS_COLOR[self as usize].store(color::OFF, Ordering::SeqCst)
+    }
+
+    /// Lets the environment decide whether to write color escape sequences for
+    /// this stream (default).
+    pub fn auto_color(self) {
+        IS_COLOR[self as usize].store(color::AUTO, Ordering::SeqCst)
+    }
+
+    /// Checks if the color escape sequences are enabled for this stream.
+    ///
+    /// An override from [with_color] or [override_color] on the current thread
+    /// takes precedence over the stream's policy.
+    pub fn is_coloring(self) -> bool {
+        self.decision() != Decision::Off
+    }
+
+    /// Decides whether to write color escape sequences for this stream.
+    fn decision(self) -> Decision {
+        if let Some(enabled) = Override::current().color {
+            return Decision::forced(enabled);
+        }
+        static AUTO_COLOR: [OnceLock<Decision>; 2] = [OnceLock::new(), OnceLock::new()];
+        match IS_COLOR[self as usize].load(Ordering::SeqCst) {
+            color::OFF => Decision::Off,
+            color::ON => Decision::Forced,
+            _ => *AUTO_COLOR[self as usize].get_or_init(|| {
+                let is_terminal = match self {
+                    Self::Stdout => std::io::stdout().is_terminal(),
+                    Self::Stderr => std::io::stderr().is_terminal(),
+                };
+                resolve_auto(|key| std::env::var(key).ok(), is_terminal)
+            }),
+        }
+    }
+
+    /// Returns the [ColorLevel] to format with for this stream.
+    ///
+    /// Forcing colors on uses at least [ColorLevel::Ansi16], even when the
+    /// terminal is detected as not supporting any.
+    fn color_level(self) -> ColorLevel {
+        match self.decision() {
+            Decision::Off => ColorLevel::None,
+            Decision::Terminal => color_level(),
+            Decision::Forced => color_level().max(ColorLevel::Ansi16),
+        }
+    }
+}
+
+/// Whether a [Stream] writes color escape sequences and why.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Decision {
+    Off,
+    /// Colors are written because the stream is a terminal.
+    Terminal,
+    /// Colors are written regardless of the terminal.
+    Forced,
+}
+
+impl Decision {
+    fn forced(enabled: bool) -> Self {
+        match enabled {
+            true => Self::Forced,
+            false => Self::Off,
+        }
+    }
+}
+
+/// Settings on the current thread that take precedence over the global ones.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+struct Override {
+    color: Option<bool>,
+    level: Option<ColorLevel>,
+}
+
+impl Override {
+    /// Combines the overrides of every live [ColorGuard] on the current
+    /// thread, the newest taking precedence.
+    fn current() -> Self {
+        OVERRIDES.with(|overrides| {
+            overrides
+                .borrow()
+                .iter()
+                .fold(Self::default(), |below, (_, o)| Self {
+                    color: o.color.or(below.color),
+                    level: o.level.or(below.level),
+                })
+        })
+    }
+}
+
+thread_local! {
+    /// Overrides from the oldest to the newest guard, keyed by guard id.
+    static OVERRIDES: RefCell<Vec<(u64, Override)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_GUARD: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Removes its thread-local color settings when dropped.
+///
+/// Guards may be dropped in any order: dropping one only removes its own
+/// setting, and the newest guard still alive decides each setting.
+///
+/// Created by [override_color] and [override_color_level].
+#[must_use = "the override is removed as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ColorGuard {
+    id: u64,
+    // the guard must be dropped on the thread it was created on
+    _not_send: PhantomData<*const ()>,
+}
+
+impl ColorGuard {
+    fn push(edit: impl FnOnce(&mut Override)) -> Self {
+        let mut next = Override::default();
+        edit(&mut next);
+        let id = NEXT_GUARD.with(|n| n.replace(n.get() + 1));
+        OVERRIDES.with(|o| o.borrow_mut().push((id, next)));
+        Self {
+            id,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl Drop for ColorGuard {
+    fn drop(&mut self) {
+        OVERRIDES.with(|o| o.borrow_mut().retain(|&(id, _)| id != self.id))
+    }
+}
+
+/// Enables or disables color escape sequences on the current thread until the
+/// returned guard is dropped, regardless of any [Stream]'s policy.
+pub fn override_color(enabled: bool) -> ColorGuard {
+    ColorGuard::push(|o| o.color = Some(enabled))
+}
+
+/// Uses `level` on the current thread until the returned guard is dropped,
+/// regardless of the detected [ColorLevel].
+pub fn override_color_level(level: ColorLevel) -> ColorGuard {
+    ColorGuard::push(|o| o.level = Some(level))
+}
+
+/// Runs `f` with color escape sequences enabled or disabled on the current thread.
+///
+/// ```
+/// use crayon::Color;
+///
+/// let plain = crayon::with_color(false, || "text".red().to_string());
+/// assert_eq!(plain, "text");
+/// ```
+pub fn with_color<R>(enabled: bool, f: impl FnOnce() -> R) -> R {
+    let _guard = override_color(enabled);
+    f()
+}
+
+/// Runs `f` with colors formatted at `level` on the current thread.
+pub fn with_color_level<R>(level: ColorLevel, f: impl FnOnce() -> R) -> R {
+    let _guard = override_color_level(level);
+    f()
+}
+
+/// Decides if color escape sequences should be written using `var` to look up
+/// environment variables and `is_terminal` to describe the output stream.
+///
+/// - `NO_COLOR` set to a non-empty value always disables color
+/// - `CLICOLOR_FORCE` set to a value other than `0` always enables color
+/// - `CLICOLOR` set to `0` disables color
+/// - otherwise color is enabled only for terminals
+fn resolve_auto<F: Fn(&str) -> Option<String>>(var: F, is_terminal: bool) -> Decision {
+    let is_set = |key| var(key).is_some_and(|v| !v.is_empty());
+    if is_set("NO_COLOR") {
+        Decision::Off
+    } else if is_set("CLICOLOR_FORCE") && var("CLICOLOR_FORCE").as_deref() != Some("0") {
+        Decision::Forced
+    } else if var("CLICOLOR").as_deref() == Some("0") || !is_terminal {
+        Decision::Off
+    } else {
+        Decision::Terminal
+    }
+}
+
+/// Global variable storing the [ColorLevel] used when formatting a [ColoredString].
+static COLOR_LEVEL: AtomicU8 = AtomicU8::new(level::UNKNOWN);
+
+mod level {
+    pub const UNKNOWN: u8 = u8::MAX;
+}
+
+/// The range of colors a terminal is capable of displaying.
+///
+/// Levels are ordered from least to most capable, so a terminal supporting a
+/// level also supports every level below it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum ColorLevel {
+    /// No escape sequences are supported.
+    None,
+    /// The 8 standard colors and their bright variants.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+impl ColorLevel {
+    /// Detects the color level of the terminal from the process environment.
+    pub fn detect() -> Self {
+        Self::from_env(|key| std::env::var(key).ok())
+    }
+
+    /// Detects the color level of the terminal using `var` to look up
+    /// environment variables.
+    ///
+    /// Consults `COLORTERM`, `TERM`, `TERM_PROGRAM` and `WT_SESSION`.
+    pub fn from_env<F: Fn(&str) -> Option<String>>(var: F) -> Self {
+        if let Some(colorterm) = var("COLORTERM") {
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return Self::TrueColor;
+            }
+        }
+        let term = var("TERM").unwrap_or_default();
+        if term == "dumb" {
+            return Self::None;
+        }
+        if var("WT_SESSION").is_some() {
+            return Self::TrueColor;
+        }
+        match var("TERM_PROGRAM").as_deref() {
+            Some("iTerm.app") | Some("WezTerm") | Some("vscode") => return Self::TrueColor,
+            Some("Apple_Terminal") => return Self::Ansi256,
+            _ => (),
+        }
+        if term.ends_with("-direct") || term.contains("truecolor") || term.contains("24bit") {
+            Self::TrueColor
+        } else if term.contains("256") {
+            Self::Ansi256
+        } else {
+            // basic colors are assumed to work anywhere escape sequences are allowed
+            Self::Ansi16
+        }
+    }
+
+    fn from_u8(n: u8) -> Self {
+        match n {
+            0 => Self::None,
+            1 => Self::Ansi16,
+            2 => Self::Ansi256,
+            _ => Self::TrueColor,
+        }
+    }
+}
+
+/// Overrides the detected [ColorLevel] used when formatting a [ColoredString].
+pub fn set_color_level(level: ColorLevel) {
+    COLOR_LEVEL.store(level as u8, Ordering::SeqCst)
 }
 
-/// Checks if the color escape sequences are enabled.
-fn is_coloring() -> bool {
-    match IS_COLOR.load(Ordering::SeqCst) {
-        color::ON => true,
-        color::OFF => false,
+/// Returns the [ColorLevel] used when formatting a [ColoredString].
+///
+/// The level is detected from the environment on first use unless it was set
+/// with [set_color_level] or overridden on the current thread.
+pub fn color_level() -> ColorLevel {
+    if let Some(level) = Override::current().level {
+        return level;
+    }
+    match COLOR_LEVEL.load(Ordering::SeqCst) {
+        level::UNKNOWN => {
+            let detected = ColorLevel::detect();
+            // keep any level set by another thread in the meantime
+            let _ = COLOR_LEVEL.compare_exchange(
+                level::UNKNOWN,
+                detected as u8,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+            ColorLevel::from_u8(COLOR_LEVEL.load(Ordering::SeqCst))
+        }
+        n => ColorLevel::from_u8(n),
     }
 }
 
 mod palette {
+    use std::sync::OnceLock;
+
+    use super::lab::Lab;
+    use super::ColorLevel;
+
     // standard text decorators
-    #[derive(Debug, PartialEq, Clone)]
+    #[derive(Debug, PartialEq, Clone, Copy)]
     pub struct Bold;
 
-    #[derive(Debug, PartialEq, Clone)]
-    pub struct Underline;
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum Underline {
+        Single,
+        Double,
+        Curly,
+        Dotted,
+        Dashed,
+    }
 
-    #[derive(Debug, PartialEq, Clone)]
+    #[derive(Debug, PartialEq, Clone, Copy)]
     pub struct Reversed;
 
-    // standard 8 ANSI foreground colors
-    #[derive(Debug, PartialEq, Clone)]
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Dim;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Italic;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Blink;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Hidden;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Strikethrough;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Overline;
+
+    // standard 8 ANSI foreground colors and their aixterm bright variants
+    #[derive(Debug, PartialEq, Clone, Copy)]
     pub enum Fg {
         Black,
         Red,
@@ -50,11 +409,27 @@ mod palette {
         Magenta,
         Cyan,
         White,
+        BrightBlack,
+        BrightRed,
+        BrightGreen,
+        BrightYellow,
+        BrightBlue,
+        BrightMagenta,
+        BrightCyan,
+        BrightWhite,
+        Index(u8),
+        Rgb(u8, u8, u8),
+    }
+
+    // underline colors from the 256-color palette or 24-bit RGB
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub enum UnderlineColor {
         Index(u8),
+        Rgb(u8, u8, u8),
     }
 
-    // standard 8 ANSI background colors
-    #[derive(Debug, PartialEq, Clone)]
+    // standard 8 ANSI background colors and their aixterm bright variants
+    #[derive(Debug, PartialEq, Clone, Copy)]
     pub enum Bg {
         Black,
         Red,
@@ -64,41 +439,223 @@ mod palette {
         Magenta,
         Cyan,
         White,
+        BrightBlack,
+        BrightRed,
+        BrightGreen,
+        BrightYellow,
+        BrightBlue,
+        BrightMagenta,
+        BrightCyan,
+        BrightWhite,
         Index(u8),
+        Rgb(u8, u8, u8),
     }
 
-    fn downscale(n: &u8) -> u8 {
-        if n < &(95 - 20) {
-            0
-        } else if n < &(135 - 20) {
-            1
-        } else if n < &(175 - 20) {
-            2
-        } else if n < &(215 - 20) {
-            3
-        } else if n < &(255 - 20) {
-            4
-        } else {
-            5
+    // colors of the 16 standard and bright colors in xterm's default theme
+    const ANSI_RGB: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    // component levels of the 6x6x6 color cube
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    /// Gets the red, green and blue components of a 256-color index.
+    pub fn index_rgb(i: u8) -> (u8, u8, u8) {
+        match i {
+            0..=15 => ANSI_RGB[i as usize],
+            16..=231 => {
+                let c = (i - 16) as usize;
+                (
+                    CUBE_LEVELS[c / 36],
+                    CUBE_LEVELS[(c / 6) % 6],
+                    CUBE_LEVELS[c % 6],
+                )
+            }
+            _ => {
+                let p = 8 + (i - 232) * 10;
+                (p, p, p)
+            }
         }
     }
 
-    fn is_greyscale(r: &u8, g: &u8, b: &u8) -> bool {
-        r == g && g == b
+    /// Finds the 256-color index of the color cube or greyscale ramp that
+    /// looks closest to an RGB color, by the CIELAB color difference.
+    ///
+    /// The 16 standard colors are left out since terminal themes change them.
+    pub fn compute_index(r: &u8, g: &u8, b: &u8) -> u8 {
+        static TABLE: OnceLock<[Lab; 240]> = OnceLock::new();
+        let table = TABLE.get_or_init(|| {
+            std::array::from_fn(|i| {
+                let (r, g, b) = index_rgb(i as u8 + 16);
+                Lab::from_rgb(r, g, b)
+            })
+        });
+        nearest(table, Lab::from_rgb(*r, *g, *b)) as u8 + 16
+    }
+
+    /// Finds the position of the color in `table` closest to `target`.
+    fn nearest(table: &[Lab], target: Lab) -> usize {
+        (0..table.len())
+            .min_by(|&x, &y| {
+                target
+                    .distance(&table[x])
+                    .total_cmp(&target.distance(&table[y]))
+            })
+            .unwrap_or(0)
     }
 
-    fn downscale_grey(p: &u8) -> u8 {
-        p / 11
+    /// Finds the offset of the standard or bright color that looks closest to
+    /// an RGB color, by the CIELAB color difference against xterm's default
+    /// theme.
+    pub fn nearest_ansi16(r: &u8, g: &u8, b: &u8) -> u8 {
+        static TABLE: OnceLock<[Lab; 16]> = OnceLock::new();
+        let table = TABLE.get_or_init(|| ANSI_RGB.map(|(r, g, b)| Lab::from_rgb(r, g, b)));
+        nearest(table, Lab::from_rgb(*r, *g, *b)) as u8
     }
 
-    pub fn compute_index(r: &u8, g: &u8, b: &u8) -> u8 {
-        if is_greyscale(r, g, b) == true {
-            match g {
-                255 => 231,
-                _ => downscale_grey(g) + 232
+    /// Finds the offset of the standard or bright color closest to a
+    /// 256-color index.
+    fn reduce_index(i: &u8) -> u8 {
+        match i {
+            0..=15 => *i,
+            _ => {
+                let (r, g, b) = index_rgb(*i);
+                nearest_ansi16(&r, &g, &b)
+            }
+        }
+    }
+
+    impl Fg {
+        /// Picks one of the 8 standard colors by its offset 0-7, or one of
+        /// their bright variants by 8-15.
+        pub const fn from_offset(n: u8) -> Self {
+            match n {
+                0 => Self::Black,
+                1 => Self::Red,
+                2 => Self::Green,
+                3 => Self::Yellow,
+                4 => Self::Blue,
+                5 => Self::Magenta,
+                6 => Self::Cyan,
+                7 => Self::White,
+                8 => Self::BrightBlack,
+                9 => Self::BrightRed,
+                10 => Self::BrightGreen,
+                11 => Self::BrightYellow,
+                12 => Self::BrightBlue,
+                13 => Self::BrightMagenta,
+                14 => Self::BrightCyan,
+                _ => Self::BrightWhite,
+            }
+        }
+
+        /// Falls back to the nearest 256-color index for a truecolor value.
+        pub fn quantize(&self) -> Self {
+            match self {
+                Self::Rgb(r, g, b) => Self::Index(compute_index(r, g, b)),
+                _ => *self,
+            }
+        }
+
+        /// Converts a 256-color index or truecolor value to the nearest of the
+        /// 8 standard colors and their bright variants.
+        pub fn to_ansi16(self) -> Self {
+            match self {
+                Self::Index(i) => Self::from_offset(reduce_index(&i)),
+                Self::Rgb(r, g, b) => Self::from_offset(nearest_ansi16(&r, &g, &b)),
+                _ => self,
+            }
+        }
+
+        /// Converts the color to the closest one representable at `level`.
+        pub fn downgrade(&self, level: ColorLevel) -> Self {
+            match level {
+                ColorLevel::TrueColor | ColorLevel::None => *self,
+                ColorLevel::Ansi256 => self.quantize(),
+                ColorLevel::Ansi16 => self.to_ansi16(),
+            }
+        }
+    }
+
+    impl UnderlineColor {
+        /// Converts the color to the closest one representable at `level`.
+        ///
+        /// Returns `None` when `level` cannot color underlines separately.
+        pub fn downgrade(&self, level: ColorLevel) -> Option<Self> {
+            match (level, self) {
+                (ColorLevel::None | ColorLevel::Ansi16, _) => None,
+                (ColorLevel::Ansi256, Self::Rgb(r, g, b)) => {
+                    Some(Self::Index(compute_index(r, g, b)))
+                }
+                _ => Some(*self),
+            }
+        }
+    }
+
+    impl Bg {
+        /// Picks one of the 8 standard colors by its offset 0-7, or one of
+        /// their bright variants by 8-15.
+        pub const fn from_offset(n: u8) -> Self {
+            match n {
+                0 => Self::Black,
+                1 => Self::Red,
+                2 => Self::Green,
+                3 => Self::Yellow,
+                4 => Self::Blue,
+                5 => Self::Magenta,
+                6 => Self::Cyan,
+                7 => Self::White,
+                8 => Self::BrightBlack,
+                9 => Self::BrightRed,
+                10 => Self::BrightGreen,
+                11 => Self::BrightYellow,
+                12 => Self::BrightBlue,
+                13 => Self::BrightMagenta,
+                14 => Self::BrightCyan,
+                _ => Self::BrightWhite,
+            }
+        }
+
+        /// Falls back to the nearest 256-color index for a truecolor value.
+        pub fn quantize(&self) -> Self {
+            match self {
+                Self::Rgb(r, g, b) => Self::Index(compute_index(r, g, b)),
+                _ => *self,
+            }
+        }
+
+        /// Converts a 256-color index or truecolor value to the nearest of the
+        /// 8 standard colors and their bright variants.
+        pub fn to_ansi16(self) -> Self {
+            match self {
+                Self::Index(i) => Self::from_offset(reduce_index(&i)),
+                Self::Rgb(r, g, b) => Self::from_offset(nearest_ansi16(&r, &g, &b)),
+                _ => self,
+            }
+        }
+
+        /// Converts the color to the closest one representable at `level`.
+        pub fn downgrade(&self, level: ColorLevel) -> Self {
+            match level {
+                ColorLevel::TrueColor | ColorLevel::None => *self,
+                ColorLevel::Ansi256 => self.quantize(),
+                ColorLevel::Ansi16 => self.to_ansi16(),
             }
-        } else {
-            ((downscale(r) * 36) + (downscale(g) * 6) + downscale(b)) + 16
         }
     }
 }
@@ -111,7 +668,22 @@ impl Display for palette::Bold {
 
 impl Display for palette::Underline {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "4")
+        match self {
+            Self::Single => write!(f, "4"),
+            Self::Double => write!(f, "4:2"),
+            Self::Curly => write!(f, "4:3"),
+            Self::Dotted => write!(f, "4:4"),
+            Self::Dashed => write!(f, "4:5"),
+        }
+    }
+}
+
+impl Display for palette::UnderlineColor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(i) => write!(f, "58;5;{}", i),
+            Self::Rgb(r, g, b) => write!(f, "58;2;{};{};{}", r, g, b),
+        }
     }
 }
 
@@ -121,86 +693,339 @@ impl Display for palette::Reversed {
     }
 }
 
+impl Display for palette::Dim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "2")
+    }
+}
+
+impl Display for palette::Italic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "3")
+    }
+}
+
+impl Display for palette::Blink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "5")
+    }
+}
+
+impl Display for palette::Hidden {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "8")
+    }
+}
+
+impl Display for palette::Strikethrough {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "9")
+    }
+}
+
+impl Display for palette::Overline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "53")
+    }
+}
+
 impl Display for palette::Fg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buf = String::new();
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Black => "30",
-                Self::Red => "31",
-                Self::Green => "32",
-                Self::Yellow => "33",
-                Self::Blue => "34",
-                Self::Magenta => "35",
-                Self::Cyan => "36",
-                Self::White => "37",
-                Self::Index(i) => {
-                    buf.push_str(&format!("38;5;{}", i));
-                    &buf
-                },
-            }
-        )
+        match self {
+            Self::Black => write!(f, "30"),
+            Self::Red => write!(f, "31"),
+            Self::Green => write!(f, "32"),
+            Self::Yellow => write!(f, "33"),
+            Self::Blue => write!(f, "34"),
+            Self::Magenta => write!(f, "35"),
+            Self::Cyan => write!(f, "36"),
+            Self::White => write!(f, "37"),
+            Self::BrightBlack => write!(f, "90"),
+            Self::BrightRed => write!(f, "91"),
+            Self::BrightGreen => write!(f, "92"),
+            Self::BrightYellow => write!(f, "93"),
+            Self::BrightBlue => write!(f, "94"),
+            Self::BrightMagenta => write!(f, "95"),
+            Self::BrightCyan => write!(f, "96"),
+            Self::BrightWhite => write!(f, "97"),
+            Self::Index(i) => write!(f, "38;5;{}", i),
+            Self::Rgb(r, g, b) => write!(f, "38;2;{};{};{}", r, g, b),
+        }
     }
 }
 
 impl Display for palette::Bg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut buf = String::new();
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::Black => "40",
-                Self::Red => "41",
-                Self::Green => "42",
-                Self::Yellow => "43",
-                Self::Blue => "44",
-                Self::Magenta => "45",
-                Self::Cyan => "46",
-                Self::White => "47",
-                Self::Index(i) => {
-                    buf.push_str(&format!("48;5;{}", i));
-                    &buf
-                },
-            }
-        )
+        match self {
+            Self::Black => write!(f, "40"),
+            Self::Red => write!(f, "41"),
+            Self::Green => write!(f, "42"),
+            Self::Yellow => write!(f, "43"),
+            Self::Blue => write!(f, "44"),
+            Self::Magenta => write!(f, "45"),
+            Self::Cyan => write!(f, "46"),
+            Self::White => write!(f, "47"),
+            Self::BrightBlack => write!(f, "100"),
+            Self::BrightRed => write!(f, "101"),
+            Self::BrightGreen => write!(f, "102"),
+            Self::BrightYellow => write!(f, "103"),
+            Self::BrightBlue => write!(f, "104"),
+            Self::BrightMagenta => write!(f, "105"),
+            Self::BrightCyan => write!(f, "106"),
+            Self::BrightWhite => write!(f, "107"),
+            Self::Index(i) => write!(f, "48;5;{}", i),
+            Self::Rgb(r, g, b) => write!(f, "48;2;{};{};{}", r, g, b),
+        }
     }
 }
 
 const ESC_SEQ: &str = "\u{001b}";
-const RESET_CODE: &str = "0";
+
+/// Writes a URL into an OSC 8 sequence with its control characters
+/// percent-encoded, so that a BEL, ESC or C1 control in the URL cannot end the
+/// sequence early.
+struct OscUrl<'a>(&'a str);
+
+impl Display for OscUrl<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut run = 0;
+        for (i, c) in self.0.char_indices().filter(|(_, c)| c.is_control()) {
+            f.write_str(&self.0[run..i])?;
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                write!(f, "%{:02X}", byte)?;
+            }
+            run = i + c.len_utf8();
+        }
+        f.write_str(&self.0[run..])
+    }
+}
 
 use palette::*;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct Code {
     fg: Option<Fg>,
     bg: Option<Bg>,
     bold: Option<Bold>,
     underline: Option<Underline>,
+    underline_color: Option<UnderlineColor>,
     reversed: Option<Reversed>,
+    dim: Option<Dim>,
+    italic: Option<Italic>,
+    blink: Option<Blink>,
+    hidden: Option<Hidden>,
+    strikethrough: Option<Strikethrough>,
+    overline: Option<Overline>,
 }
 
 impl Code {
-    fn is_decorated(&self) -> bool {
-        self.fg.is_some()
-            || self.bg.is_some()
-            || self.bold.is_some()
-            || self.underline.is_some()
-            || self.reversed.is_some()
-    }
-
-    fn new() -> Self {
+    const fn new() -> Self {
         Self {
             fg: None,
             bg: None,
             bold: None,
             underline: None,
+            underline_color: None,
             reversed: None,
+            dim: None,
+            italic: None,
+            blink: None,
+            hidden: None,
+            strikethrough: None,
+            overline: None,
+        }
+    }
+
+    /// Converts the colors to the closest ones representable at `level`.
+    fn downgrade(&self, level: ColorLevel) -> Self {
+        Self {
+            fg: self.fg.as_ref().map(|c| c.downgrade(level)),
+            bg: self.bg.as_ref().map(|c| c.downgrade(level)),
+            underline_color: self
+                .underline_color
+                .as_ref()
+                .and_then(|c| c.downgrade(level)),
+            ..*self
+        }
+    }
+
+    /// Layers `top` over the code, keeping only the attributes `top` leaves unset.
+    fn overlay(&self, top: &Code) -> Self {
+        Self {
+            fg: top.fg.or(self.fg),
+            bg: top.bg.or(self.bg),
+            bold: top.bold.or(self.bold),
+            underline: top.underline.or(self.underline),
+            underline_color: top.underline_color.or(self.underline_color),
+            reversed: top.reversed.or(self.reversed),
+            dim: top.dim.or(self.dim),
+            italic: top.italic.or(self.italic),
+            blink: top.blink.or(self.blink),
+            hidden: top.hidden.or(self.hidden),
+            strikethrough: top.strikethrough.or(self.strikethrough),
+            overline: top.overline.or(self.overline),
+        }
+    }
+
+    /// Writes a single combined SGR escape sequence that changes the terminal
+    /// from the attributes of `self` to the attributes of `next`.
+    ///
+    /// Attributes `next` leaves unset are turned off with their targeted reset
+    /// codes rather than resetting everything. Nothing is written if the codes
+    /// are equal.
+    fn write_transition<W: std::fmt::Write>(&self, next: &Code, w: &mut W) -> std::fmt::Result {
+        let mut sgr = Sgr::new(w);
+        // bold and dim share the same reset code
+        let is_intensity_off = (self.bold.is_some() && next.bold.is_none())
+            || (self.dim.is_some() && next.dim.is_none());
+        sgr.toggle(&self.bg, &next.bg, "49")?;
+        sgr.toggle(&self.fg, &next.fg, "39")?;
+        if is_intensity_off {
+            sgr.param("22")?;
+        }
+        if let Some(bold) = &next.bold {
+            if self.bold.is_none() || is_intensity_off {
+                sgr.param(bold)?;
+            }
+        }
+        sgr.toggle(&self.underline, &next.underline, "24")?;
+        sgr.toggle(&self.underline_color, &next.underline_color, "59")?;
+        sgr.toggle(&self.reversed, &next.reversed, "27")?;
+        if let Some(dim) = &next.dim {
+            if self.dim.is_none() || is_intensity_off {
+                sgr.param(dim)?;
+            }
+        }
+        sgr.toggle(&self.italic, &next.italic, "23")?;
+        sgr.toggle(&self.blink, &next.blink, "25")?;
+        sgr.toggle(&self.hidden, &next.hidden, "28")?;
+        sgr.toggle(&self.strikethrough, &next.strikethrough, "29")?;
+        sgr.toggle(&self.overline, &next.overline, "55")?;
+        sgr.finish()
+    }
+
+    /// Writes `data` while the terminal is set to this code, such as text
+    /// formatted from other colored text.
+    ///
+    /// Every SGR sequence embedded in `data` is replaced so that the inner
+    /// attributes are layered over this code and the code is restored after
+    /// them, rather than being reset. Other escape sequences are kept as is.
+    fn write_nested<W: std::fmt::Write>(
+        &self,
+        data: &str,
+        level: ColorLevel,
+        w: &mut W,
+    ) -> std::fmt::Result {
+        let mut inner = Code::new();
+        let mut current = *self;
+        let mut tokens = ansi::Tokens::new(data);
+        let mut rest = tokens.as_str();
+        while let Some(token) = tokens.next() {
+            let raw = &rest[..rest.len() - tokens.as_str().len()];
+            rest = tokens.as_str();
+            match token {
+                ansi::Token::Text(text) => {
+                    let next = self.overlay(&inner.downgrade(level));
+                    current.write_transition(&next, w)?;
+                    current = next;
+                    w.write_str(text)?;
+                }
+                ansi::Token::Csi {
+                    params,
+                    action: 'm',
+                } if parse::is_sgr(params) => parse::apply_sgr(&mut inner, params),
+                _ => w.write_str(raw)?,
+            }
+        }
+        current.write_transition(self, w)
+    }
+}
+
+/// Joins SGR parameters into a single escape sequence as they are written.
+struct Sgr<'w, W: std::fmt::Write> {
+    w: &'w mut W,
+    is_empty: bool,
+}
+
+impl<'w, W: std::fmt::Write> Sgr<'w, W> {
+    fn new(w: &'w mut W) -> Self {
+        Self { w, is_empty: true }
+    }
+
+    fn param<P: Display>(&mut self, param: P) -> std::fmt::Result {
+        match self.is_empty {
+            true => write!(self.w, "{}[{}", ESC_SEQ, param)?,
+            false => write!(self.w, ";{}", param)?,
+        }
+        self.is_empty = false;
+        Ok(())
+    }
+
+    /// Writes the attribute `to` if it changed from `from`, or `off` if it was removed.
+    fn toggle<P: Display + PartialEq>(
+        &mut self,
+        from: &Option<P>,
+        to: &Option<P>,
+        off: &str,
+    ) -> std::fmt::Result {
+        match (from, to) {
+            (_, Some(p)) if from != to => self.param(p),
+            (Some(_), None) => self.param(off),
+            _ => Ok(()),
+        }
+    }
+
+    fn finish(self) -> std::fmt::Result {
+        match self.is_empty {
+            true => Ok(()),
+            false => self.w.write_str("m"),
+        }
+    }
+}
+
+/// Fill written around text to meet the width and alignment of a formatter.
+struct Padding {
+    fill: char,
+    before: usize,
+    after: usize,
+}
+
+impl Padding {
+    /// Pads text taking up `width` columns, aligning it to the left unless the
+    /// formatter asks otherwise.
+    fn new(f: &std::fmt::Formatter<'_>, width: usize) -> Self {
+        let padding = f.width().map_or(0, |w| w.saturating_sub(width));
+        let before = match f.align() {
+            Some(std::fmt::Alignment::Right) => padding,
+            Some(std::fmt::Alignment::Center) => padding / 2,
+            _ => 0,
+        };
+        Self {
+            fill: f.fill(),
+            before,
+            after: padding - before,
         }
     }
+
+    fn write_before(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (0..self.before).try_for_each(|_| f.write_char(self.fill))
+    }
+
+    fn write_after(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (0..self.after).try_for_each(|_| f.write_char(self.fill))
+    }
+}
+
+/// Cuts `data` down to the precision of a formatter in columns and measures
+/// it when the formatter asks for a width.
+fn fit<'s>(data: &'s str, f: &std::fmt::Formatter<'_>) -> (&'s str, Padding) {
+    let (data, width) = match f.precision() {
+        Some(columns) => width::truncate(data, columns),
+        None if f.width().is_some() => (data, width::str_width(data)),
+        None => (data, 0),
+    };
+    (data, Padding::new(f, width))
 }
 
 pub trait AsAnsi {
@@ -212,12 +1037,29 @@ pub trait AsAnsi {
 
     /// References the original [String] contents without ANSI codes.
     fn get_data(&self) -> &str;
+
+    /// References the URL the contents link to as an OSC 8 terminal hyperlink.
+    ///
+    /// A `None` value indicates the contents are not linked.
+    fn as_link(&self) -> Option<&str> {
+        None
+    }
+
+    /// Counts the terminal columns the contents take up.
+    ///
+    /// Any ANSI escape sequences in the contents are ignored, East Asian wide
+    /// characters and emoji count as two columns and combining characters as
+    /// none.
+    fn display_width(&self) -> usize {
+        width::str_width(self.get_data())
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct ColoredString {
     data: String,
     code: Code,
+    link: Option<String>,
 }
 
 impl ColoredString {
@@ -225,6 +1067,7 @@ impl ColoredString {
         Self {
             data: String::new(),
             code: Code::new(),
+            link: None,
         }
     }
 
@@ -235,65 +1078,291 @@ impl ColoredString {
         Self {
             data: s.into(),
             code: Code::new(),
+            link: None,
         }
     }
 }
 
-impl Display for ColoredString {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match is_coloring() {
-            true => {
-                write!(
-                    f,
-                    "{}{}{}{}{}{}{}",
-                    if let Some(bg) = &self.code.bg {
-                        ESC_SEQ.to_owned() + "[" + &bg.to_string() + "m"
-                    } else {
-                        String::new()
-                    },
-                    if let Some(fg) = &self.code.fg {
-                        ESC_SEQ.to_owned() + "[" + &fg.to_string() + "m"
-                    } else {
-                        String::new()
-                    },
-                    if let Some(dc) = &self.code.bold {
-                        ESC_SEQ.to_owned() + "[" + &dc.to_string() + "m"
-                    } else {
-                        String::new()
-                    },
-                    if let Some(dc) = &self.code.underline {
-                        ESC_SEQ.to_owned() + "[" + &dc.to_string() + "m"
-                    } else {
-                        String::new()
-                    },
-                    if let Some(dc) = &self.code.reversed {
-                        ESC_SEQ.to_owned() + "[" + &dc.to_string() + "m"
-                    } else {
-                        String::new()
-                    },
-                    self.data,
-                    if self.code.is_decorated() == true {
-                        ESC_SEQ.to_owned() + "[" + RESET_CODE + "m"
-                    } else {
-                        String::new()
-                    }
-                )
-            }
-            false => write!(f, "{}", self.data),
-        }
+impl Default for ColoredString {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl AsAnsi for ColoredString {
-    /// References the ANSI terminal [Code] commands.
-    fn as_code(&self) -> Option<&Code> {
-        Some(&self.code)
+impl ColoredString {
+    /// Formats the string using the color policy of `stream` rather than stdout.
+    ///
+    /// ```
+    /// use crayon::{Color, Stream};
+    ///
+    /// eprintln!("{}", "error".red().for_stream(Stream::Stderr));
+    /// ```
+    pub fn for_stream(&self, stream: Stream) -> Rendered<'_> {
+        self.as_colored_str().for_stream(stream)
     }
 
-    /// References the original [String] contents without ANSI codes.
+    /// Borrows the string's data and codes without copying them.
+    pub fn as_colored_str(&self) -> ColoredStr<'_> {
+        ColoredStr {
+            data: &self.data,
+            code: self.code,
+            link: self.link.as_deref(),
+        }
+    }
+
+    /// Replaces the code with the result of applying `edit` to it as a [Style].
+    fn restyled(mut self, edit: impl FnOnce(Style) -> Style) -> Self {
+        self.code = edit(Style { code: self.code }).code;
+        self
+    }
+
+    /// Turns the contents into a terminal hyperlink to `url` using OSC 8.
+    pub fn link(mut self, url: &str) -> Self {
+        self.link = Some(url.to_string());
+        self
+    }
+
+    /// Converts the colors to the closest ones representable at `level`, such
+    /// as the nearest of the 16 standard and bright colors at
+    /// [ColorLevel::Ansi16].
+    ///
+    /// Colors are left as they are at [ColorLevel::None].
+    pub fn downgrade(mut self, level: ColorLevel) -> Self {
+        self.code = self.code.downgrade(level);
+        self
+    }
+
+    /// Cuts the contents down to at most `width` terminal columns without
+    /// splitting a grapheme cluster or an escape sequence.
+    pub fn truncate(mut self, width: usize) -> Self {
+        let end = width::truncate(&self.data, width).0.len();
+        self.data.truncate(end);
+        self
+    }
+
+    /// Pads the contents with unstyled spaces on the left up to `width`
+    /// terminal columns, as [StyledText::pad_left] does.
+    ///
+    /// The padding stays outside of the codes, just like the padding from the
+    /// width of a formatter.
+    ///
+    /// ```
+    /// use crayon::Color;
+    ///
+    /// assert_eq!(
+    ///     "ok".bg_red().pad_left(4).to_string(),
+    ///     format!("{:>4}", "ok".bg_red())
+    /// );
+    /// ```
+    pub fn pad_left(self, width: usize) -> StyledText {
+        StyledText::from(self).pad_left(width)
+    }
+
+    /// Pads the contents with unstyled spaces on the right up to `width`
+    /// terminal columns, as [StyledText::pad_right] does.
+    pub fn pad_right(self, width: usize) -> StyledText {
+        StyledText::from(self).pad_right(width)
+    }
+
+    /// Pads the contents with unstyled spaces on both sides up to `width`
+    /// terminal columns, with any odd space on the right, as
+    /// [StyledText::pad_center] does.
+    pub fn pad_center(self, width: usize) -> StyledText {
+        StyledText::from(self).pad_center(width)
+    }
+
+    /// Cuts the contents down to at most `width` terminal columns like
+    /// [ColoredString::truncate], ending them with `ellipsis` when they do
+    /// not fit.
+    pub fn ellipsize(mut self, width: usize, ellipsis: &str) -> Self {
+        if self.display_width() <= width {
+            return self;
+        }
+        let (ellipsis, columns) = width::truncate(ellipsis, width);
+        let end = width::truncate(&self.data, width - columns).0.len();
+        self.data.truncate(end);
+        self.data.push_str(ellipsis);
+        self
+    }
+}
+
+impl Display for ColoredString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_colored_str()
+            .write_ansi(f, Stream::Stdout.color_level())
+    }
+}
+
+/// A borrowed counterpart to [ColoredString] that stores ANSI terminal codes
+/// alongside a `&str` instead of an owned [String].
+///
+/// Its combinators consume and return the value without allocating.
+///
+/// ```
+/// use crayon::ColoredStr;
+///
+/// let line = String::from("a very long log line");
+/// println!("{}", ColoredStr::new(&line).yellow().bold());
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ColoredStr<'a> {
+    data: &'a str,
+    code: Code,
+    link: Option<&'a str>,
+}
+
+impl<'a> ColoredStr<'a> {
+    pub fn new(data: &'a str) -> Self {
+        Self {
+            data,
+            code: Code::new(),
+            link: None,
+        }
+    }
+
+    /// Formats the string using the color policy of `stream` rather than stdout.
+    pub fn for_stream(self, stream: Stream) -> Rendered<'a> {
+        Rendered {
+            inner: Inner::Str(self),
+            stream,
+        }
+    }
+
+    /// Replaces the code with the result of applying `edit` to it as a [Style].
+    fn restyled(mut self, edit: impl FnOnce(Style) -> Style) -> Self {
+        self.code = edit(Style { code: self.code }).code;
+        self
+    }
+
+    /// Turns the contents into a terminal hyperlink to `url` using OSC 8.
+    pub fn link(mut self, url: &'a str) -> Self {
+        self.link = Some(url);
+        self
+    }
+
+    /// Converts the colors to the closest ones representable at `level`, such
+    /// as the nearest of the 16 standard and bright colors at
+    /// [ColorLevel::Ansi16].
+    ///
+    /// Colors are left as they are at [ColorLevel::None].
+    pub fn downgrade(mut self, level: ColorLevel) -> Self {
+        self.code = self.code.downgrade(level);
+        self
+    }
+
+    /// Writes the string with its ANSI codes converted for `level`.
+    ///
+    /// The string is truncated and padded in columns to the precision, width,
+    /// fill and alignment of the formatter, with the padding left unstyled
+    /// outside of the codes.
+    fn write_ansi(&self, f: &mut std::fmt::Formatter<'_>, level: ColorLevel) -> std::fmt::Result {
+        let (data, padding) = fit(self.data, f);
+        padding.write_before(f)?;
+        if level == ColorLevel::None {
+            f.write_str(data)?;
+            return padding.write_after(f);
+        }
+        let code = self.code.downgrade(level);
+        if let Some(url) = self.link {
+            write!(f, "{}]8;;{}{}\\", ESC_SEQ, OscUrl(url), ESC_SEQ)?;
+        }
+        Code::new().write_transition(&code, f)?;
+        code.write_nested(data, level, f)?;
+        code.write_transition(&Code::new(), f)?;
+        if self.link.is_some() {
+            write!(f, "{}]8;;{}\\", ESC_SEQ, ESC_SEQ)?;
+        }
+        padding.write_after(f)
+    }
+}
+
+impl<'a> From<&'a str> for ColoredStr<'a> {
+    fn from(data: &'a str) -> Self {
+        Self::new(data)
+    }
+}
+
+impl From<ColoredStr<'_>> for ColoredString {
+    fn from(s: ColoredStr<'_>) -> Self {
+        Self {
+            data: s.data.to_string(),
+            code: s.code,
+            link: s.link.map(String::from),
+        }
+    }
+}
+
+impl Display for ColoredStr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_ansi(f, Stream::Stdout.color_level())
+    }
+}
+
+impl AsAnsi for ColoredStr<'_> {
+    /// References the ANSI terminal [Code] commands.
+    fn as_code(&self) -> Option<&Code> {
+        Some(&self.code)
+    }
+
+    /// References the original [String] contents without ANSI codes.
+    fn get_data(&self) -> &str {
+        self.data
+    }
+
+    /// References the URL the contents link to as an OSC 8 terminal hyperlink.
+    fn as_link(&self) -> Option<&str> {
+        self.link
+    }
+}
+
+/// A [ColoredString], [ColoredStr] or [StyledText] formatted according to the
+/// color policy of a [Stream].
+#[derive(Debug, Clone, Copy)]
+pub struct Rendered<'a> {
+    inner: Inner<'a>,
+    stream: Stream,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Inner<'a> {
+    Str(ColoredStr<'a>),
+    Text(&'a StyledText),
+}
+
+impl<'a> Rendered<'a> {
+    fn text(text: &'a StyledText, stream: Stream) -> Self {
+        Self {
+            inner: Inner::Text(text),
+            stream,
+        }
+    }
+}
+
+impl Display for Rendered<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let level = self.stream.color_level();
+        match &self.inner {
+            Inner::Str(s) => s.write_ansi(f, level),
+            Inner::Text(t) => t.write_ansi(f, level),
+        }
+    }
+}
+
+impl AsAnsi for ColoredString {
+    /// References the ANSI terminal [Code] commands.
+    fn as_code(&self) -> Option<&Code> {
+        Some(&self.code)
+    }
+
+    /// References the original [String] contents without ANSI codes.
     fn get_data(&self) -> &str {
         &self.data
     }
+
+    /// References the URL the contents link to as an OSC 8 terminal hyperlink.
+    fn as_link(&self) -> Option<&str> {
+        self.link.as_deref()
+    }
 }
 
 impl<T: AsRef<str>> AsAnsi for T {
@@ -311,7 +1380,19 @@ impl<T: AsRef<str>> AsAnsi for T {
 pub trait Color<T: Display + AsAnsi> {
     fn bold(&self) -> ColoredString;
     fn underline(&self) -> ColoredString;
+    fn double_underline(&self) -> ColoredString;
+    fn curly_underline(&self) -> ColoredString;
+    fn dotted_underline(&self) -> ColoredString;
+    fn dashed_underline(&self) -> ColoredString;
+    fn underline_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString;
+    fn underline_wheel(&self, i: u8) -> ColoredString;
     fn reversed(&self) -> ColoredString;
+    fn dim(&self) -> ColoredString;
+    fn italic(&self) -> ColoredString;
+    fn blink(&self) -> ColoredString;
+    fn hidden(&self) -> ColoredString;
+    fn strikethrough(&self) -> ColoredString;
+    fn overline(&self) -> ColoredString;
 
     fn black(&self) -> ColoredString;
     fn red(&self) -> ColoredString;
@@ -321,6 +1402,14 @@ pub trait Color<T: Display + AsAnsi> {
     fn magenta(&self) -> ColoredString;
     fn cyan(&self) -> ColoredString;
     fn white(&self) -> ColoredString;
+    fn bright_black(&self) -> ColoredString;
+    fn bright_red(&self) -> ColoredString;
+    fn bright_green(&self) -> ColoredString;
+    fn bright_yellow(&self) -> ColoredString;
+    fn bright_blue(&self) -> ColoredString;
+    fn bright_magenta(&self) -> ColoredString;
+    fn bright_cyan(&self) -> ColoredString;
+    fn bright_white(&self) -> ColoredString;
     fn rgb(&self, r: u8, g: u8, b: u8) -> ColoredString;
     fn wheel(&self, i: u8) -> ColoredString;
 
@@ -332,400 +1421,872 @@ pub trait Color<T: Display + AsAnsi> {
     fn bg_magenta(&self) -> ColoredString;
     fn bg_cyan(&self) -> ColoredString;
     fn bg_white(&self) -> ColoredString;
+    fn bg_bright_black(&self) -> ColoredString;
+    fn bg_bright_red(&self) -> ColoredString;
+    fn bg_bright_green(&self) -> ColoredString;
+    fn bg_bright_yellow(&self) -> ColoredString;
+    fn bg_bright_blue(&self) -> ColoredString;
+    fn bg_bright_magenta(&self) -> ColoredString;
+    fn bg_bright_cyan(&self) -> ColoredString;
+    fn bg_bright_white(&self) -> ColoredString;
     fn bg_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString;
     fn bg_wheel(&self, i: u8) -> ColoredString;
+
+    /// Sets the foreground to any [Swatch].
+    fn fg(&self, color: Swatch) -> ColoredString;
+    /// Sets the background to any [Swatch].
+    fn bg(&self, color: Swatch) -> ColoredString;
+    /// Sets the foreground to a color parsed from a hex color, `rgb(r, g, b)`,
+    /// `ansi(n)` or a color name, as [Swatch] parses it.
+    ///
+    /// ```
+    /// use crayon::Color;
+    ///
+    /// assert_eq!("ok".fg_str("#00ff00").unwrap(), "ok".rgb(0, 255, 0));
+    /// assert!("ok".fg_str("#00ff0").is_err());
+    /// ```
+    fn fg_str(&self, color: &str) -> Result<ColoredString, ParseColorError>;
+    /// Sets the background to a color parsed as [Color::fg_str] does.
+    fn bg_str(&self, color: &str) -> Result<ColoredString, ParseColorError>;
+
+    /// Colors each grapheme cluster along a [Gradient], replacing the
+    /// foreground or background color while keeping the other codes.
+    ///
+    /// ```
+    /// use crayon::{Color, Rgb};
+    ///
+    /// let banner = "crayon".gradient(&[Rgb::new(255, 0, 0), Rgb::new(0, 0, 255)]);
+    /// assert_eq!(banner.spans().count(), 6);
+    /// ```
+    fn gradient<'g>(&self, gradient: impl Into<Gradient<'g>>) -> StyledText;
+
+    /// Turns the contents into a terminal hyperlink to `url` using OSC 8.
+    fn link(&self, url: &str) -> ColoredString;
+}
+
+/// Copies the data of `src` into a [ColoredString] whose code starts from the
+/// code of `src` and is then modified by `edit`.
+fn restyle<T: AsAnsi + ?Sized>(src: &T, edit: impl FnOnce(&mut Code)) -> ColoredString {
+    let mut code = match src.as_code() {
+        Some(c) => *c,
+        None => Code::new(),
+    };
+    edit(&mut code);
+    ColoredString {
+        data: src.get_data().to_string(),
+        code,
+        link: src.as_link().map(String::from),
+    }
 }
 
 impl<T: Display + AsAnsi> Color<T> for T {
     fn bold(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: code.bg,
-                bold: Some(Bold),
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+        restyle(self, |c| c.bold = Some(Bold))
     }
 
     fn underline(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: code.bg,
-                bold: code.bold,
-                underline: Some(Underline),
-                reversed: code.reversed,
-            },
-        }
+        restyle(self, |c| c.underline = Some(Underline::Single))
+    }
+
+    fn double_underline(&self) -> ColoredString {
+        restyle(self, |c| c.underline = Some(Underline::Double))
+    }
+
+    fn curly_underline(&self) -> ColoredString {
+        restyle(self, |c| c.underline = Some(Underline::Curly))
+    }
+
+    fn dotted_underline(&self) -> ColoredString {
+        restyle(self, |c| c.underline = Some(Underline::Dotted))
+    }
+
+    fn dashed_underline(&self) -> ColoredString {
+        restyle(self, |c| c.underline = Some(Underline::Dashed))
+    }
+
+    fn underline_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        restyle(self, |c| {
+            c.underline_color = Some(UnderlineColor::Rgb(r, g, b))
+        })
+    }
+
+    fn underline_wheel(&self, i: u8) -> ColoredString {
+        restyle(self, |c| c.underline_color = Some(UnderlineColor::Index(i)))
     }
 
     fn reversed(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: Some(Reversed),
-            },
-        }
+        restyle(self, |c| c.reversed = Some(Reversed))
+    }
+
+    fn dim(&self) -> ColoredString {
+        restyle(self, |c| c.dim = Some(Dim))
+    }
+
+    fn italic(&self) -> ColoredString {
+        restyle(self, |c| c.italic = Some(Italic))
+    }
+
+    fn blink(&self) -> ColoredString {
+        restyle(self, |c| c.blink = Some(Blink))
+    }
+
+    fn hidden(&self) -> ColoredString {
+        restyle(self, |c| c.hidden = Some(Hidden))
+    }
+
+    fn strikethrough(&self) -> ColoredString {
+        restyle(self, |c| c.strikethrough = Some(Strikethrough))
+    }
+
+    fn overline(&self) -> ColoredString {
+        restyle(self, |c| c.overline = Some(Overline))
     }
 
     fn rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new()
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Index(palette::compute_index(&r, &g, &b))),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            }
-        }
+        restyle(self, |c| c.fg = Some(Fg::Rgb(r, g, b)))
     }
 
     fn wheel(&self, i: u8) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new()
-        };
+        restyle(self, |c| c.fg = Some(Fg::Index(i)))
+    }
+
+    fn black(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Black))
+    }
+
+    fn red(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Red))
+    }
+
+    fn green(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Green))
+    }
+
+    fn yellow(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Yellow))
+    }
+
+    fn blue(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Blue))
+    }
+
+    fn magenta(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Magenta))
+    }
+
+    fn cyan(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::Cyan))
+    }
+
+    fn white(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::White))
+    }
+
+    fn bright_black(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightBlack))
+    }
+
+    fn bright_red(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightRed))
+    }
+
+    fn bright_green(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightGreen))
+    }
+
+    fn bright_yellow(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightYellow))
+    }
+
+    fn bright_blue(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightBlue))
+    }
+
+    fn bright_magenta(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightMagenta))
+    }
+
+    fn bright_cyan(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightCyan))
+    }
+
+    fn bright_white(&self) -> ColoredString {
+        restyle(self, |c| c.fg = Some(Fg::BrightWhite))
+    }
+
+    fn bg_black(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Black))
+    }
+
+    fn bg_red(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Red))
+    }
+
+    fn bg_green(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Green))
+    }
+
+    fn bg_yellow(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Yellow))
+    }
+
+    fn bg_blue(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Blue))
+    }
+
+    fn bg_magenta(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Magenta))
+    }
+
+    fn bg_cyan(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Cyan))
+    }
+
+    fn bg_white(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::White))
+    }
+
+    fn bg_bright_black(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightBlack))
+    }
+
+    fn bg_bright_red(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightRed))
+    }
+
+    fn bg_bright_green(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightGreen))
+    }
+
+    fn bg_bright_yellow(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightYellow))
+    }
+
+    fn bg_bright_blue(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightBlue))
+    }
+
+    fn bg_bright_magenta(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightMagenta))
+    }
+
+    fn bg_bright_cyan(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightCyan))
+    }
+
+    fn bg_bright_white(&self) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::BrightWhite))
+    }
+
+    fn bg_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Rgb(r, g, b)))
+    }
+
+    fn bg_wheel(&self, i: u8) -> ColoredString {
+        restyle(self, |c| c.bg = Some(Bg::Index(i)))
+    }
+
+    fn fg(&self, color: Swatch) -> ColoredString {
+        restyle(self, |c| c.fg = Some(color.fg()))
+    }
+
+    fn bg(&self, color: Swatch) -> ColoredString {
+        restyle(self, |c| c.bg = Some(color.bg()))
+    }
+
+    fn fg_str(&self, color: &str) -> Result<ColoredString, ParseColorError> {
+        Ok(self.fg(color.parse()?))
+    }
+
+    fn bg_str(&self, color: &str) -> Result<ColoredString, ParseColorError> {
+        Ok(self.bg(color.parse()?))
+    }
+
+    fn gradient<'g>(&self, gradient: impl Into<Gradient<'g>>) -> StyledText {
+        let gradient = gradient.into();
+        let text = restyle(self, |c| match gradient.background {
+            true => c.bg = None,
+            false => c.fg = None,
+        });
+        gradient.apply(&text.into())
+    }
+
+    fn link(&self, url: &str) -> ColoredString {
         ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Index(i)),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
+            link: Some(url.to_string()),
+            ..restyle(self, |_| ())
+        }
+    }
+}
+
+/// Implements consuming combinators mirroring the [Color] trait for a type
+/// with a `restyled` method, so chaining them reuses the existing data.
+///
+/// On a [StyledText] the combinators act as an outer style that every span
+/// inherits unless the span sets the same attribute itself.
+macro_rules! impl_combinators {
+    ($($lt:lifetime)?, $t:ty) => {
+        impl$(<$lt>)? $t {
+            pub fn bold(self) -> Self {
+                self.restyled(Style::bold)
+            }
+
+            pub fn underline(self) -> Self {
+                self.restyled(Style::underline)
+            }
+
+            pub fn double_underline(self) -> Self {
+                self.restyled(Style::double_underline)
+            }
+
+            pub fn curly_underline(self) -> Self {
+                self.restyled(Style::curly_underline)
+            }
+
+            pub fn dotted_underline(self) -> Self {
+                self.restyled(Style::dotted_underline)
+            }
+
+            pub fn dashed_underline(self) -> Self {
+                self.restyled(Style::dashed_underline)
+            }
+
+            pub fn reversed(self) -> Self {
+                self.restyled(Style::reversed)
+            }
+
+            pub fn dim(self) -> Self {
+                self.restyled(Style::dim)
+            }
+
+            pub fn italic(self) -> Self {
+                self.restyled(Style::italic)
+            }
+
+            pub fn blink(self) -> Self {
+                self.restyled(Style::blink)
+            }
+
+            pub fn hidden(self) -> Self {
+                self.restyled(Style::hidden)
+            }
+
+            pub fn strikethrough(self) -> Self {
+                self.restyled(Style::strikethrough)
+            }
+
+            pub fn overline(self) -> Self {
+                self.restyled(Style::overline)
+            }
+
+            pub fn black(self) -> Self {
+                self.restyled(Style::black)
+            }
+
+            pub fn red(self) -> Self {
+                self.restyled(Style::red)
+            }
+
+            pub fn green(self) -> Self {
+                self.restyled(Style::green)
+            }
+
+            pub fn yellow(self) -> Self {
+                self.restyled(Style::yellow)
+            }
+
+            pub fn blue(self) -> Self {
+                self.restyled(Style::blue)
+            }
+
+            pub fn magenta(self) -> Self {
+                self.restyled(Style::magenta)
+            }
+
+            pub fn cyan(self) -> Self {
+                self.restyled(Style::cyan)
+            }
+
+            pub fn white(self) -> Self {
+                self.restyled(Style::white)
+            }
+
+            pub fn bright_black(self) -> Self {
+                self.restyled(Style::bright_black)
+            }
+
+            pub fn bright_red(self) -> Self {
+                self.restyled(Style::bright_red)
+            }
+
+            pub fn bright_green(self) -> Self {
+                self.restyled(Style::bright_green)
+            }
+
+            pub fn bright_yellow(self) -> Self {
+                self.restyled(Style::bright_yellow)
+            }
+
+            pub fn bright_blue(self) -> Self {
+                self.restyled(Style::bright_blue)
+            }
+
+            pub fn bright_magenta(self) -> Self {
+                self.restyled(Style::bright_magenta)
+            }
+
+            pub fn bright_cyan(self) -> Self {
+                self.restyled(Style::bright_cyan)
+            }
+
+            pub fn bright_white(self) -> Self {
+                self.restyled(Style::bright_white)
+            }
+
+            pub fn bg_black(self) -> Self {
+                self.restyled(Style::bg_black)
+            }
+
+            pub fn bg_red(self) -> Self {
+                self.restyled(Style::bg_red)
+            }
+
+            pub fn bg_green(self) -> Self {
+                self.restyled(Style::bg_green)
+            }
+
+            pub fn bg_yellow(self) -> Self {
+                self.restyled(Style::bg_yellow)
+            }
+
+            pub fn bg_blue(self) -> Self {
+                self.restyled(Style::bg_blue)
+            }
+
+            pub fn bg_magenta(self) -> Self {
+                self.restyled(Style::bg_magenta)
+            }
+
+            pub fn bg_cyan(self) -> Self {
+                self.restyled(Style::bg_cyan)
+            }
+
+            pub fn bg_white(self) -> Self {
+                self.restyled(Style::bg_white)
+            }
+
+            pub fn bg_bright_black(self) -> Self {
+                self.restyled(Style::bg_bright_black)
+            }
+
+            pub fn bg_bright_red(self) -> Self {
+                self.restyled(Style::bg_bright_red)
+            }
+
+            pub fn bg_bright_green(self) -> Self {
+                self.restyled(Style::bg_bright_green)
+            }
+
+            pub fn bg_bright_yellow(self) -> Self {
+                self.restyled(Style::bg_bright_yellow)
+            }
+
+            pub fn bg_bright_blue(self) -> Self {
+                self.restyled(Style::bg_bright_blue)
+            }
+
+            pub fn bg_bright_magenta(self) -> Self {
+                self.restyled(Style::bg_bright_magenta)
+            }
+
+            pub fn bg_bright_cyan(self) -> Self {
+                self.restyled(Style::bg_bright_cyan)
+            }
+
+            pub fn bg_bright_white(self) -> Self {
+                self.restyled(Style::bg_bright_white)
+            }
+
+            pub fn underline_rgb(self, r: u8, g: u8, b: u8) -> Self {
+                self.restyled(|s| s.underline_rgb(r, g, b))
+            }
+
+            pub fn rgb(self, r: u8, g: u8, b: u8) -> Self {
+                self.restyled(|s| s.rgb(r, g, b))
+            }
+
+            pub fn bg_rgb(self, r: u8, g: u8, b: u8) -> Self {
+                self.restyled(|s| s.bg_rgb(r, g, b))
+            }
+
+            pub fn underline_wheel(self, i: u8) -> Self {
+                self.restyled(|s| s.underline_wheel(i))
+            }
+
+            pub fn wheel(self, i: u8) -> Self {
+                self.restyled(|s| s.wheel(i))
+            }
+
+            pub fn bg_wheel(self, i: u8) -> Self {
+                self.restyled(|s| s.bg_wheel(i))
+            }
+
+            pub fn fg(self, color: Swatch) -> Self {
+                self.restyled(|s| s.fg(color))
+            }
+
+            pub fn bg(self, color: Swatch) -> Self {
+                self.restyled(|s| s.bg(color))
+            }
+
+            pub fn fg_str(self, color: &str) -> Result<Self, ParseColorError> {
+                Ok(self.fg(color.parse()?))
+            }
+
+            pub fn bg_str(self, color: &str) -> Result<Self, ParseColorError> {
+                Ok(self.bg(color.parse()?))
             }
         }
+    };
+}
+
+impl_combinators!(, ColoredString);
+impl_combinators!('a, ColoredStr<'a>);
+impl_combinators!(, StyledText);
+
+/// A reusable set of ANSI terminal codes that is independent of any text.
+///
+/// Styles are `Copy` and can be built in a `const` context to be applied to
+/// many strings with [Style::paint].
+///
+/// ```
+/// use crayon::Style;
+///
+/// const ERROR: Style = Style::new().red().bold();
+///
+/// println!("{}: {}", ERROR.paint("error"), "file not found");
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Style {
+    code: Code,
+}
+
+impl Style {
+    /// Creates a style without any ANSI terminal codes.
+    pub const fn new() -> Self {
+        Self { code: Code::new() }
     }
 
-    fn black(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Black),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
+    /// References the ANSI terminal [Code] commands.
+    pub const fn as_code(&self) -> &Code {
+        &self.code
+    }
+
+    /// Applies the style to `text`, keeping any codes of `text` the style
+    /// leaves unset.
+    pub fn paint<T: AsAnsi>(&self, text: T) -> ColoredString {
+        restyle(&text, |c| *c = c.overlay(&self.code))
+    }
+
+    /// Applies the style to `text` without copying it.
+    pub const fn paint_str<'a>(&self, text: &'a str) -> ColoredStr<'a> {
+        ColoredStr {
+            data: text,
+            code: self.code,
+            link: None,
         }
     }
 
-    fn red(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Red),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
+    /// Converts the colors to the closest ones representable at `level`, such
+    /// as the nearest of the 16 standard and bright colors at
+    /// [ColorLevel::Ansi16].
+    ///
+    /// Colors are left as they are at [ColorLevel::None].
+    pub fn downgrade(self, level: ColorLevel) -> Self {
+        Self {
+            code: self.code.downgrade(level),
         }
     }
 
-    fn green(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Green),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bold(mut self) -> Self {
+        self.code.bold = Some(Bold);
+        self
+    }
+
+    pub const fn underline(mut self) -> Self {
+        self.code.underline = Some(Underline::Single);
+        self
+    }
+
+    pub const fn double_underline(mut self) -> Self {
+        self.code.underline = Some(Underline::Double);
+        self
+    }
+
+    pub const fn curly_underline(mut self) -> Self {
+        self.code.underline = Some(Underline::Curly);
+        self
+    }
+
+    pub const fn dotted_underline(mut self) -> Self {
+        self.code.underline = Some(Underline::Dotted);
+        self
+    }
+
+    pub const fn dashed_underline(mut self) -> Self {
+        self.code.underline = Some(Underline::Dashed);
+        self
+    }
+
+    pub const fn underline_rgb(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.code.underline_color = Some(UnderlineColor::Rgb(r, g, b));
+        self
+    }
+
+    pub const fn underline_wheel(mut self, i: u8) -> Self {
+        self.code.underline_color = Some(UnderlineColor::Index(i));
+        self
+    }
+
+    pub const fn reversed(mut self) -> Self {
+        self.code.reversed = Some(Reversed);
+        self
+    }
+
+    pub const fn dim(mut self) -> Self {
+        self.code.dim = Some(Dim);
+        self
+    }
+
+    pub const fn italic(mut self) -> Self {
+        self.code.italic = Some(Italic);
+        self
+    }
+
+    pub const fn blink(mut self) -> Self {
+        self.code.blink = Some(Blink);
+        self
+    }
+
+    pub const fn hidden(mut self) -> Self {
+        self.code.hidden = Some(Hidden);
+        self
+    }
+
+    pub const fn strikethrough(mut self) -> Self {
+        self.code.strikethrough = Some(Strikethrough);
+        self
+    }
+
+    pub const fn overline(mut self) -> Self {
+        self.code.overline = Some(Overline);
+        self
+    }
+
+    pub const fn black(mut self) -> Self {
+        self.code.fg = Some(Fg::Black);
+        self
+    }
+
+    pub const fn red(mut self) -> Self {
+        self.code.fg = Some(Fg::Red);
+        self
+    }
+
+    pub const fn green(mut self) -> Self {
+        self.code.fg = Some(Fg::Green);
+        self
+    }
+
+    pub const fn yellow(mut self) -> Self {
+        self.code.fg = Some(Fg::Yellow);
+        self
+    }
+
+    pub const fn blue(mut self) -> Self {
+        self.code.fg = Some(Fg::Blue);
+        self
+    }
+
+    pub const fn magenta(mut self) -> Self {
+        self.code.fg = Some(Fg::Magenta);
+        self
+    }
+
+    pub const fn cyan(mut self) -> Self {
+        self.code.fg = Some(Fg::Cyan);
+        self
+    }
+
+    pub const fn white(mut self) -> Self {
+        self.code.fg = Some(Fg::White);
+        self
+    }
+
+    pub const fn bright_black(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightBlack);
+        self
+    }
+
+    pub const fn bright_red(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightRed);
+        self
+    }
+
+    pub const fn bright_green(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightGreen);
+        self
+    }
+
+    pub const fn bright_yellow(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightYellow);
+        self
+    }
+
+    pub const fn bright_blue(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightBlue);
+        self
+    }
+
+    pub const fn bright_magenta(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightMagenta);
+        self
+    }
+
+    pub const fn bright_cyan(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightCyan);
+        self
+    }
+
+    pub const fn bright_white(mut self) -> Self {
+        self.code.fg = Some(Fg::BrightWhite);
+        self
+    }
+
+    pub const fn rgb(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.code.fg = Some(Fg::Rgb(r, g, b));
+        self
+    }
+
+    pub const fn wheel(mut self, i: u8) -> Self {
+        self.code.fg = Some(Fg::Index(i));
+        self
+    }
+
+    pub const fn bg_black(mut self) -> Self {
+        self.code.bg = Some(Bg::Black);
+        self
+    }
+
+    pub const fn bg_red(mut self) -> Self {
+        self.code.bg = Some(Bg::Red);
+        self
+    }
+
+    pub const fn bg_green(mut self) -> Self {
+        self.code.bg = Some(Bg::Green);
+        self
+    }
+
+    pub const fn bg_yellow(mut self) -> Self {
+        self.code.bg = Some(Bg::Yellow);
+        self
+    }
+
+    pub const fn bg_blue(mut self) -> Self {
+        self.code.bg = Some(Bg::Blue);
+        self
+    }
+
+    pub const fn bg_magenta(mut self) -> Self {
+        self.code.bg = Some(Bg::Magenta);
+        self
+    }
+
+    pub const fn bg_cyan(mut self) -> Self {
+        self.code.bg = Some(Bg::Cyan);
+        self
     }
 
-    fn yellow(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Yellow),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_white(mut self) -> Self {
+        self.code.bg = Some(Bg::White);
+        self
     }
 
-    fn blue(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Blue),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_black(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightBlack);
+        self
     }
 
-    fn magenta(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Magenta),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_red(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightRed);
+        self
     }
 
-    fn cyan(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::Cyan),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_green(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightGreen);
+        self
     }
 
-    fn white(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: Some(Fg::White),
-                bg: code.bg,
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_yellow(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightYellow);
+        self
     }
 
-    fn bg_black(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Black),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_blue(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightBlue);
+        self
     }
 
-    fn bg_red(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Red),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_magenta(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightMagenta);
+        self
     }
 
-    fn bg_green(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Green),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_cyan(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightCyan);
+        self
     }
 
-    fn bg_yellow(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Yellow),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_bright_white(mut self) -> Self {
+        self.code.bg = Some(Bg::BrightWhite);
+        self
     }
 
-    fn bg_blue(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Blue),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_rgb(mut self, r: u8, g: u8, b: u8) -> Self {
+        self.code.bg = Some(Bg::Rgb(r, g, b));
+        self
     }
 
-    fn bg_magenta(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Magenta),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg_wheel(mut self, i: u8) -> Self {
+        self.code.bg = Some(Bg::Index(i));
+        self
     }
 
-    fn bg_cyan(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Cyan),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn fg(mut self, color: Swatch) -> Self {
+        self.code.fg = Some(color.fg());
+        self
     }
 
-    fn bg_white(&self) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new(),
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::White),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            },
-        }
+    pub const fn bg(mut self, color: Swatch) -> Self {
+        self.code.bg = Some(color.bg());
+        self
     }
 
-    fn bg_rgb(&self, r: u8, g: u8, b: u8) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new()
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Index(palette::compute_index(&r, &g, &b))),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            }
-        }
+    /// Sets the foreground to a color parsed as [Color::fg_str] does.
+    pub fn fg_str(self, color: &str) -> Result<Self, ParseColorError> {
+        Ok(self.fg(color.parse()?))
     }
 
-    fn bg_wheel(&self, i: u8) -> ColoredString {
-        let code = match self.as_code() {
-            Some(c) => c.clone(),
-            None => Code::new()
-        };
-        ColoredString {
-            data: self.get_data().to_string(),
-            code: Code {
-                fg: code.fg,
-                bg: Some(Bg::Index(i)),
-                bold: code.bold,
-                underline: code.underline,
-                reversed: code.reversed,
-            }
-        }
+    /// Sets the background to a color parsed as [Color::fg_str] does.
+    pub fn bg_str(self, color: &str) -> Result<Self, ParseColorError> {
+        Ok(self.bg(color.parse()?))
+    }
+}
+
+impl From<Style> for Code {
+    fn from(style: Style) -> Self {
+        style.code
     }
 }
 
@@ -738,11 +2299,15 @@ mod tests {
     const BOLD_CODE_LEN: usize = 4;
     const UNDERLINE_CODE_LEN: usize = 4;
     const REVERSED_CODE_LEN: usize = 4;
-    const RESET_CODE_LEN: usize = 4;
+    const RESET_CODE_LEN: usize = 5;
     const NO_CODE_LEN: usize = 0;
+    // each additional code in a combined sequence replaces `ESC[` and `m` with `;`
+    const COMBINED_CODE_SAVINGS: usize = 2;
 
     #[test]
     fn ut_red() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
         let text = "red".red();
         assert_ne!(text.to_string(), "red");
         assert_eq!(text.get_data(), "red");
@@ -757,7 +2322,8 @@ mod tests {
                 fg: Some(palette::Fg::Red),
                 bold: None,
                 underline: None,
-                reversed: None
+                reversed: None,
+                ..Code::new()
             }
         );
     }
@@ -790,7 +2356,8 @@ mod tests {
                 fg: None,
                 bold: None,
                 underline: None,
-                reversed: None
+                reversed: None,
+                ..Code::new()
             }
         );
 
@@ -808,13 +2375,16 @@ mod tests {
                 fg: None,
                 bold: None,
                 underline: None,
-                reversed: None
+                reversed: None,
+                ..Code::new()
             }
         );
     }
 
     #[test]
     fn ut_overlap_colors() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
         let mut text = "go".green().yellow().blue();
         assert_ne!(text.to_string(), "go");
         assert_eq!(text.get_data(), "go");
@@ -829,7 +2399,8 @@ mod tests {
                 fg: Some(palette::Fg::Blue),
                 bold: None,
                 underline: None,
-                reversed: None
+                reversed: None,
+                ..Code::new()
             }
         );
         // swap out the foreground color
@@ -838,8 +2409,12 @@ mod tests {
         assert_eq!(text.get_data(), "go");
         assert_eq!(
             text.to_string().len(),
-            FG_CODE_LEN + BG_CODE_LEN + text.get_data().len() + RESET_CODE_LEN
+            FG_CODE_LEN + BG_CODE_LEN - COMBINED_CODE_SAVINGS
+                + text.get_data().len()
+                + 2 * RESET_CODE_LEN
+                - COMBINED_CODE_SAVINGS
         );
+        assert_eq!(text.to_string(), "\u{1b}[46;30mgo\u{1b}[49;39m");
         assert_eq!(
             text.as_code().unwrap(),
             &Code {
@@ -847,25 +2422,30 @@ mod tests {
                 fg: Some(palette::Fg::Black),
                 bold: None,
                 underline: None,
-                reversed: None
+                reversed: None,
+                ..Code::new()
             }
         );
     }
 
     #[test]
     fn ut_full_code() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
         let text = "Go".blue().bold().underline().reversed().bg_white();
         assert_ne!(text.to_string(), "Go");
         assert_eq!(text.get_data(), "Go");
         assert_eq!(
             text.to_string().len(),
-            FG_CODE_LEN
-                + BG_CODE_LEN
-                + BOLD_CODE_LEN
-                + UNDERLINE_CODE_LEN
-                + REVERSED_CODE_LEN
+            FG_CODE_LEN + BG_CODE_LEN + BOLD_CODE_LEN + UNDERLINE_CODE_LEN + REVERSED_CODE_LEN
+                - 4 * COMBINED_CODE_SAVINGS
                 + text.get_data().len()
-                + RESET_CODE_LEN
+                + 5 * RESET_CODE_LEN
+                - 4 * COMBINED_CODE_SAVINGS
+        );
+        assert_eq!(
+            text.to_string(),
+            "\u{1b}[47;34;1;4;7mGo\u{1b}[49;39;22;24;27m"
         );
         assert_eq!(
             text.as_code().unwrap(),
@@ -873,20 +2453,686 @@ mod tests {
                 bg: Some(palette::Bg::White),
                 fg: Some(palette::Fg::Blue),
                 bold: Some(palette::Bold),
-                underline: Some(palette::Underline),
-                reversed: Some(palette::Reversed)
+                underline: Some(palette::Underline::Single),
+                reversed: Some(palette::Reversed),
+                ..Code::new()
+            }
+        );
+    }
+
+    #[test]
+    fn ut_attributes() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        let text = "diff"
+            .italic()
+            .dim()
+            .blink()
+            .strikethrough()
+            .hidden()
+            .overline();
+        assert_eq!(text.get_data(), "diff");
+        assert_eq!(
+            text.as_code().unwrap(),
+            &Code {
+                dim: Some(palette::Dim),
+                italic: Some(palette::Italic),
+                blink: Some(palette::Blink),
+                hidden: Some(palette::Hidden),
+                strikethrough: Some(palette::Strikethrough),
+                overline: Some(palette::Overline),
+                ..Code::new()
+            }
+        );
+        assert_eq!(
+            text.to_string(),
+            "\u{1b}[2;3;5;8;9;53mdiff\u{1b}[22;23;25;28;29;55m"
+        );
+        // keeps previously applied codes
+        let text = "x".red().bold().italic();
+        assert_eq!(text.clone(), text);
+        assert_eq!(text.as_code().unwrap().fg, Some(palette::Fg::Red));
+        assert_eq!(text.as_code().unwrap().bold, Some(palette::Bold));
+        assert_eq!(text.as_code().unwrap().italic, Some(palette::Italic));
+        assert_ne!(text, "x".red().bold());
+    }
+
+    #[test]
+    fn ut_underline_styles() {
+        let text = "err".curly_underline().underline_rgb(255, 0, 0);
+        assert_eq!(
+            text.as_code().unwrap(),
+            &Code {
+                underline: Some(palette::Underline::Curly),
+                underline_color: Some(palette::UnderlineColor::Rgb(255, 0, 0)),
+                ..Code::new()
+            }
+        );
+        with_color_level(ColorLevel::TrueColor, || {
+            with_color(true, || {
+                assert_eq!(text.to_string(), "\u{1b}[4:3;58;2;255;0;0merr\u{1b}[24;59m")
+            })
+        });
+        with_color_level(ColorLevel::Ansi256, || {
+            with_color(true, || {
+                assert_eq!(text.to_string(), "\u{1b}[4:3;58;5;196merr\u{1b}[24;59m")
+            })
+        });
+        // basic terminals cannot color underlines
+        with_color_level(ColorLevel::Ansi16, || {
+            with_color(true, || {
+                assert_eq!(text.to_string(), "\u{1b}[4:3merr\u{1b}[24m")
+            })
+        });
+        assert_eq!(palette::Underline::Single.to_string(), "4");
+        assert_eq!(palette::Underline::Double.to_string(), "4:2");
+        assert_eq!(palette::Underline::Dotted.to_string(), "4:4");
+        assert_eq!(palette::Underline::Dashed.to_string(), "4:5");
+        assert_eq!(palette::UnderlineColor::Index(9).to_string(), "58;5;9");
+        // replaces the previous underline style
+        assert_eq!(
+            text.double_underline().as_code().unwrap().underline,
+            Some(palette::Underline::Double)
+        );
+        assert_eq!(
+            "x".dashed_underline()
+                .underline_wheel(3)
+                .as_code()
+                .unwrap()
+                .underline_color,
+            Some(palette::UnderlineColor::Index(3))
+        );
+    }
+
+    #[test]
+    fn ut_link() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "issue".link("https://example.com/1").cyan().underline();
+        assert_eq!(text.get_data(), "issue");
+        assert_eq!(text.as_link(), Some("https://example.com/1"));
+        assert_eq!("issue".as_link(), None);
+        with_color(true, || {
+            assert_eq!(
+                text.to_string(),
+                "\u{1b}]8;;https://example.com/1\u{1b}\\\u{1b}[36;4missue\u{1b}[39;24m\u{1b}]8;;\u{1b}\\"
+            );
+            assert_eq!(
+                "plain".link("file:///tmp/a.rs").to_string(),
+                "\u{1b}]8;;file:///tmp/a.rs\u{1b}\\plain\u{1b}]8;;\u{1b}\\"
+            );
+        });
+        with_color(false, || assert_eq!(text.to_string(), "issue"));
+        // control characters cannot end the sequence early
+        let _color = override_color(true);
+        assert_eq!(
+            "x".link("https://a.b/\u{7}\u{1b}]8;;evil\u{9c}")
+                .to_string(),
+            "\u{1b}]8;;https://a.b/%07%1B]8;;evil%C2%9C\u{1b}\\x\u{1b}]8;;\u{1b}\\"
+        );
+        assert_eq!(
+            ("x".link("\u{1b}\\") + "y").to_string(),
+            "\u{1b}]8;;%1B\\\u{1b}\\x\u{1b}]8;;\u{1b}\\y"
+        );
+        // relinks to the latest url
+        assert_eq!(
+            text.link("https://example.com/2").as_link(),
+            Some("https://example.com/2")
+        );
+    }
+
+    #[test]
+    fn ut_style() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        const ERROR: Style = Style::new().red().bold();
+        let _color = override_color(true);
+
+        let text = ERROR.paint("error");
+        assert_eq!(text.get_data(), "error");
+        assert_eq!(text, "error".red().bold());
+        assert_eq!(ERROR.paint(String::from("failed")), "failed".red().bold());
+        assert_eq!(text.to_string(), "\u{1b}[31;1merror\u{1b}[39;22m");
+        // reuses the same style
+        assert_eq!(ERROR.paint("again").as_code(), Some(ERROR.as_code()));
+        // layers over existing codes
+        assert_eq!(
+            ERROR.paint("x".bg_blue().green()),
+            "x".bg_blue().red().bold()
+        );
+        assert_eq!(Style::new().paint("plain"), ColoredString::from("plain"));
+        assert_eq!(Style::default(), Style::new());
+        assert_eq!(
+            Code::from(Style::new().bg_rgb(1, 2, 3).curly_underline()),
+            *"x".bg_rgb(1, 2, 3).curly_underline().as_code().unwrap()
+        );
+    }
+
+    #[test]
+    fn ut_consuming_combinators() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        // chaining on an owned string keeps the same buffer
+        let text = ColoredString::from("reuse");
+        let ptr = text.get_data().as_ptr();
+        let text = text.red().bold().underline().bg_rgb(1, 2, 3);
+        assert_eq!(text.get_data().as_ptr(), ptr);
+        assert_eq!(text, "reuse".red().bold().underline().bg_rgb(1, 2, 3));
+        assert_eq!(
+            text.link("https://example.com").as_link(),
+            Some("https://example.com")
+        );
+
+        // a borrowed string never copies its data
+        let line = String::from("a long log line");
+        let borrowed = ColoredStr::new(&line).yellow().bold().wheel(208);
+        assert!(std::ptr::eq(borrowed.get_data(), line.as_str()));
+        assert_eq!(
+            borrowed.to_string(),
+            "a long log line".bold().wheel(208).to_string()
+        );
+        let owned: ColoredString = borrowed.into();
+        assert_eq!(owned, "a long log line".bold().wheel(208));
+        assert_eq!(
+            Style::new().cyan().paint_str(&line),
+            ColoredStr::from(line.as_str()).cyan()
+        );
+        assert_eq!(
+            "a long log line".cyan().as_colored_str(),
+            ColoredStr::from(line.as_str()).cyan()
+        );
+        // the Color trait still works through references
+        let owned = "x".red();
+        let copied = Color::bold(&owned);
+        assert_eq!(owned.get_data(), copied.get_data());
+        assert_eq!(
+            ColoredStr::new("x")
+                .link("file:///x")
+                .for_stream(Stream::Stdout)
+                .to_string(),
+            "\u{1b}]8;;file:///x\u{1b}\\x\u{1b}]8;;\u{1b}\\"
+        );
+    }
+
+    #[test]
+    fn ut_targeted_reset() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        // an embedded string no longer resets the codes around it
+        let text = format!("pre {} post", "x".red()).bold();
+        assert_eq!(
+            text.to_string(),
+            "\u{1b}[1mpre \u{1b}[31mx\u{1b}[39m post\u{1b}[22m"
+        );
+        // the outer code is restored when the inner one sets the same attribute
+        assert_eq!(
+            format!("pre {} post", "x".blue()).red().to_string(),
+            "\u{1b}[31mpre \u{1b}[34mx\u{1b}[31m post\u{1b}[39m"
+        );
+        assert_eq!(
+            format!("pre {} post", "x".bold()).bold().to_string(),
+            "\u{1b}[1mpre x post\u{1b}[22m"
+        );
+        assert_eq!(
+            format!("a{}b", "x".rgb(255, 0, 0).bold().underline())
+                .underline()
+                .to_string(),
+            "\u{1b}[4ma\u{1b}[38;2;255;0;0;1mx\u{1b}[39;22mb\u{1b}[24m"
+        );
+        // other escape sequences are kept and nested codes are downgraded
+        with_color_level(ColorLevel::Ansi16, || {
+            assert_eq!(
+                format!("\u{1b}[2K{}", "x".rgb(255, 0, 0)).red().to_string(),
+                "\u{1b}[31m\u{1b}[2K\u{1b}[91mx\u{1b}[31m\u{1b}[39m"
+            );
+        });
+        assert_eq!(
+            (StyledText::from("a") + format!("b{}c", "x".green()).green()).to_string(),
+            "a\u{1b}[32mbxc\u{1b}[39m"
+        );
+        let full = Code {
+            fg: Some(palette::Fg::Red),
+            bold: Some(palette::Bold),
+            dim: Some(palette::Dim),
+            ..Code::new()
+        };
+        let mut buf = String::new();
+        full.write_transition(&full, &mut buf).unwrap();
+        assert_eq!(buf, "");
+        full.write_transition(&Code { bold: None, ..full }, &mut buf)
+            .unwrap();
+        assert_eq!(buf, "\u{1b}[22;2m");
+    }
+
+    #[test]
+    fn ut_bright() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        let text = "hi".bright_red().bg_bright_blue();
+        assert_eq!(text.get_data(), "hi");
+        assert_eq!(
+            text.as_code().unwrap(),
+            &Code {
+                bg: Some(palette::Bg::BrightBlue),
+                fg: Some(palette::Fg::BrightRed),
+                bold: None,
+                underline: None,
+                reversed: None,
+                ..Code::new()
+            }
+        );
+        assert_eq!(text.to_string(), "\u{1b}[104;91mhi\u{1b}[49;39m");
+        assert_eq!(
+            "x".bright_black().as_code().unwrap().fg,
+            Some(palette::Fg::BrightBlack)
+        );
+        assert_eq!(
+            "x".bright_white().as_code().unwrap().fg,
+            Some(palette::Fg::BrightWhite)
+        );
+        assert_eq!(palette::Fg::BrightWhite.to_string(), "97");
+        assert_eq!(palette::Bg::BrightBlack.to_string(), "100");
+        assert_eq!(palette::Bg::BrightWhite.to_string(), "107");
+        // bright colors are part of the 16-color palette
+        assert_eq!(
+            palette::Fg::BrightCyan.downgrade(ColorLevel::Ansi16),
+            palette::Fg::BrightCyan
+        );
+        assert_eq!(
+            palette::Fg::Index(9).downgrade(ColorLevel::Ansi16),
+            palette::Fg::BrightRed
+        );
+    }
+
+    #[test]
+    fn ut_rgb() {
+        let text = "brand".rgb(255, 136, 0).bg_rgb(18, 52, 86);
+        assert_eq!(text.get_data(), "brand");
+        assert_eq!(
+            text.as_code().unwrap(),
+            &Code {
+                bg: Some(palette::Bg::Rgb(18, 52, 86)),
+                fg: Some(palette::Fg::Rgb(255, 136, 0)),
+                bold: None,
+                underline: None,
+                reversed: None,
+                ..Code::new()
             }
         );
+        assert_eq!(palette::Fg::Rgb(255, 136, 0).to_string(), "38;2;255;136;0");
+        assert_eq!(palette::Bg::Rgb(18, 52, 86).to_string(), "48;2;18;52;86");
+        // falls back to the 256-color palette
+        assert_eq!(
+            palette::Fg::Rgb(255, 136, 0).quantize(),
+            palette::Fg::Index(208)
+        );
+        assert_eq!(palette::Bg::Rgb(0, 0, 0).quantize(), palette::Bg::Index(16));
+        assert_eq!(palette::Fg::Red.quantize(), palette::Fg::Red);
+    }
+
+    /// Creates an environment variable lookup from a list of key-value pairs.
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn ut_detect_color_level() {
+        assert_eq!(ColorLevel::from_env(env(&[])), ColorLevel::Ansi16);
+        assert_eq!(
+            ColorLevel::from_env(env(&[("TERM", "dumb")])),
+            ColorLevel::None
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[("TERM", "linux")])),
+            ColorLevel::Ansi16
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[("TERM", "xterm-256color")])),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[("TERM", "xterm-direct")])),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[
+                ("TERM", "xterm-256color"),
+                ("COLORTERM", "truecolor")
+            ])),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[
+                ("TERM", "xterm"),
+                ("TERM_PROGRAM", "Apple_Terminal")
+            ])),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(
+            ColorLevel::from_env(env(&[("WT_SESSION", "1")])),
+            ColorLevel::TrueColor
+        );
+        assert!(ColorLevel::None < ColorLevel::Ansi16);
+        assert!(ColorLevel::Ansi256 < ColorLevel::TrueColor);
+    }
+
+    #[test]
+    fn ut_auto_color() {
+        use Decision::*;
+        assert_eq!(resolve_auto(env(&[]), true), Terminal);
+        assert_eq!(resolve_auto(env(&[]), false), Off);
+        assert_eq!(resolve_auto(env(&[("NO_COLOR", "1")]), true), Off);
+        assert_eq!(resolve_auto(env(&[("NO_COLOR", "")]), true), Terminal);
+        assert_eq!(resolve_auto(env(&[("CLICOLOR_FORCE", "1")]), false), Forced);
+        assert_eq!(resolve_auto(env(&[("CLICOLOR_FORCE", "1")]), true), Forced);
+        assert_eq!(resolve_auto(env(&[("CLICOLOR_FORCE", "0")]), false), Off);
+        assert_eq!(
+            resolve_auto(env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]), false),
+            Off
+        );
+        assert_eq!(resolve_auto(env(&[("CLICOLOR", "0")]), true), Off);
+        assert_eq!(resolve_auto(env(&[("CLICOLOR", "1")]), false), Off);
+    }
+
+    #[test]
+    fn ut_forced_color_level() {
+        // a dumb terminal still gets basic colors once they are forced on
+        let _level = override_color_level(ColorLevel::None);
+        with_color(true, || {
+            assert_eq!(Stream::Stdout.color_level(), ColorLevel::Ansi16);
+            assert_eq!("ok".green().to_string(), "\u{1b}[32mok\u{1b}[39m");
+            with_color_level(ColorLevel::Ansi256, || {
+                assert_eq!(Stream::Stderr.color_level(), ColorLevel::Ansi256);
+            });
+        });
+        with_color(false, || {
+            assert_eq!(Stream::Stdout.color_level(), ColorLevel::None);
+        });
+    }
+
+    #[test]
+    fn ut_stream_policy() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "warn".yellow();
+        let previous = IS_COLOR[Stream::Stderr as usize].load(Ordering::SeqCst);
+        let stdout = Stream::Stdout.is_coloring();
+        let rendered = text.to_string();
+
+        Stream::Stderr.enable_color();
+        assert!(Stream::Stderr.is_coloring());
+        assert_eq!(
+            text.for_stream(Stream::Stderr).to_string(),
+            "\u{1b}[33mwarn\u{1b}[39m"
+        );
+        // stdout keeps its own decision
+        assert_eq!(Stream::Stdout.is_coloring(), stdout);
+        assert_eq!(text.to_string(), rendered);
+
+        Stream::Stderr.disable_color();
+        assert!(!Stream::Stderr.is_coloring());
+        assert_eq!(text.for_stream(Stream::Stderr).to_string(), "warn");
+        assert_eq!(Stream::Stdout.is_coloring(), stdout);
+        assert_eq!(text.to_string(), rendered);
+
+        IS_COLOR[Stream::Stderr as usize].store(previous, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn ut_override_color() {
+        let text = "go".green().bg_rgb(0, 0, 255);
+        with_color(true, || {
+            assert_ne!(text.to_string(), "go");
+            with_color(false, || assert_eq!(text.to_string(), "go"));
+            // restores the outer override
+            assert_ne!(text.to_string(), "go");
+            with_color_level(ColorLevel::Ansi256, || {
+                assert_eq!(text.to_string(), "\u{1b}[48;5;21;32mgo\u{1b}[49;39m");
+            });
+            let _level = override_color_level(ColorLevel::Ansi16);
+            assert_eq!(text.to_string(), "\u{1b}[44;32mgo\u{1b}[49;39m");
+            assert_eq!(
+                text.for_stream(Stream::Stderr).to_string(),
+                "\u{1b}[44;32mgo\u{1b}[49;39m"
+            );
+        });
+        // overrides do not leak onto other threads
+        let _color = override_color(false);
+        assert_eq!(
+            std::thread::spawn(|| Override::current().color)
+                .join()
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn ut_guard_order() {
+        let outer = override_color(false);
+        let level = override_color_level(ColorLevel::Ansi256);
+        let inner = override_color(true);
+        assert!(Stream::Stdout.is_coloring());
+        // dropping the outer guard first keeps the newer ones in place
+        drop(outer);
+        assert!(Stream::Stdout.is_coloring());
+        assert_eq!(color_level(), ColorLevel::Ansi256);
+        let nested = override_color(false);
+        drop(inner);
+        assert!(!Stream::Stdout.is_coloring());
+        drop(level);
+        assert_eq!(Override::current().level, None);
+        drop(nested);
+        assert_eq!(Override::current(), Override::default());
+    }
+
+    #[test]
+    fn ut_downgrade() {
+        let fg = palette::Fg::Rgb(255, 136, 0);
+        assert_eq!(fg.downgrade(ColorLevel::TrueColor), fg);
+        assert_eq!(fg.downgrade(ColorLevel::Ansi256), palette::Fg::Index(208));
+        assert_eq!(fg.downgrade(ColorLevel::Ansi16), palette::Fg::Red);
+
+        let bg = palette::Bg::Index(21);
+        assert_eq!(bg.downgrade(ColorLevel::Ansi256), bg);
+        assert_eq!(bg.downgrade(ColorLevel::Ansi16), palette::Bg::Blue);
+        assert_eq!(
+            palette::Bg::Index(1).downgrade(ColorLevel::Ansi16),
+            palette::Bg::Red
+        );
+        assert_eq!(
+            palette::Bg::Index(234).downgrade(ColorLevel::Ansi16),
+            palette::Bg::Black
+        );
+        assert_eq!(
+            palette::Bg::Index(246).downgrade(ColorLevel::Ansi16),
+            palette::Bg::BrightBlack
+        );
+        assert_eq!(
+            palette::Fg::Cyan.downgrade(ColorLevel::Ansi16),
+            palette::Fg::Cyan
+        );
+        // explicitly to the nearest of the 16 colors
+        assert_eq!(palette::Fg::Index(196).to_ansi16(), palette::Fg::BrightRed);
+        assert_eq!(
+            palette::Bg::Rgb(250, 250, 240).to_ansi16(),
+            palette::Bg::BrightWhite
+        );
+        assert_eq!(palette::Fg::Index(3).to_ansi16(), palette::Fg::Yellow);
+        assert_eq!(
+            "warn".rgb(255, 215, 0).downgrade(ColorLevel::Ansi16),
+            "warn".yellow()
+        );
+        assert_eq!(
+            Style::new()
+                .wheel(22)
+                .bg_rgb(10, 10, 10)
+                .downgrade(ColorLevel::Ansi16),
+            Style::new().green().bg_black()
+        );
+        assert_eq!(
+            ColoredStr::new("x")
+                .rgb(1, 2, 3)
+                .downgrade(ColorLevel::Ansi256),
+            ColoredStr::new("x").wheel(16)
+        );
+    }
+
+    #[test]
+    fn ut_display_width() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let _color = override_color(true);
+        let text = "red".red().bold();
+        assert_eq!(text.display_width(), 3);
+        // escapes in formatted strings take up no columns
+        assert_eq!(text.to_string().display_width(), 3);
+        assert_eq!(
+            text.link("https://example.com").to_string().display_width(),
+            3
+        );
+        assert_eq!("\u{65e5}\u{672c}".green().display_width(), 4);
+        assert_eq!("cafe\u{301}".display_width(), 4);
+        let styled = "error".red() + ": " + "\u{1f4a5}".bold();
+        assert_eq!(styled.display_width(), 9);
+        assert_eq!(styled.to_string().display_width(), 9);
+    }
+
+    #[test]
+    fn ut_format_padding() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "ok".green();
+        with_color(true, || {
+            assert_eq!(format!("{:>5}", text), "   \u{1b}[32mok\u{1b}[39m");
+            assert_eq!(format!("{:<5}|", text), "\u{1b}[32mok\u{1b}[39m   |");
+            assert_eq!(format!("{:*^7}", text), "**\u{1b}[32mok\u{1b}[39m***");
+            assert_eq!(format!("{:.1}", text), "\u{1b}[32mo\u{1b}[39m");
+            assert_eq!(format!("{:4.1}", text), "\u{1b}[32mo\u{1b}[39m   ");
+            // padding stays outside of the codes, so a background does not fill it
+            assert_eq!(
+                format!("{:^6}", "ok".bg_red().link("x")),
+                "  \u{1b}]8;;x\u{1b}\\\u{1b}[41mok\u{1b}[49m\u{1b}]8;;\u{1b}\\  "
+            );
+            assert_eq!(
+                format!("{:>4}", ColoredStr::new("ok").bg_rgb(0, 0, 255)),
+                "  \u{1b}[48;2;0;0;255mok\u{1b}[49m"
+            );
+            // a width smaller than the text does not truncate it
+            assert_eq!(format!("{:1}", text), text.to_string());
+            // padding is measured in columns rather than bytes
+            assert_eq!(
+                format!("{:>4}", ColoredStr::new("\u{65e5}").red()),
+                "  \u{1b}[31m\u{65e5}\u{1b}[39m"
+            );
+            assert_eq!(
+                format!("{:6}", text.for_stream(Stream::Stderr)),
+                "\u{1b}[32mok\u{1b}[39m    "
+            );
+        });
+        with_color(false, || {
+            assert_eq!(format!("[{:^6.3}]", "hello".red()), "[ hel  ]");
+        });
+    }
+
+    #[test]
+    fn ut_fit_columns() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "status".green().bold();
+        assert_eq!(text.clone().truncate(3), "sta".green().bold());
+        assert_eq!(text.clone().truncate(10), text);
+        assert_eq!(
+            text.clone().pad_left(8),
+            StyledText::from("  ") + text.clone()
+        );
+        assert_eq!(text.clone().pad_right(8), text.clone() + "  ");
+        assert_eq!(
+            text.clone().pad_center(9),
+            StyledText::from(" ") + text.clone() + "  "
+        );
+        assert_eq!(text.clone().pad_left(2), StyledText::from(text.clone()));
+        // padding a string or styled text renders the same bytes, with the
+        // background left off the padding
+        with_color(true, || {
+            let x = "x".bg_red();
+            let styled = StyledText::from(x.clone());
+            assert_eq!(
+                x.clone().pad_left(4).to_string(),
+                styled.clone().pad_left(4).to_string()
+            );
+            assert_eq!(
+                x.clone().pad_right(4).to_string(),
+                styled.clone().pad_right(4).to_string()
+            );
+            assert_eq!(
+                x.clone().pad_center(4).to_string(),
+                styled.clone().pad_center(4).to_string()
+            );
+            assert_eq!(x.clone().pad_left(3).to_string(), "  \u{1b}[41mx\u{1b}[49m");
+            assert_eq!(x.clone().pad_center(4).to_string(), format!("{:^4}", x));
+            assert_eq!(styled.pad_right(3).to_string(), "\u{1b}[41mx\u{1b}[49m  ");
+        });
+        assert_eq!(
+            text.clone().ellipsize(4, "\u{2026}"),
+            "sta\u{2026}".green().bold()
+        );
+        assert_eq!(text.clone().ellipsize(6, "..."), text);
+        assert_eq!(text.clone().ellipsize(2, "..."), "..".green().bold());
+        // wide characters and combined graphemes are never split
+        let wide = "\u{65e5}\u{672c}e\u{301}".red();
+        assert_eq!(wide.clone().truncate(3), "\u{65e5}".red());
+        assert_eq!(wide.clone().truncate(5), wide);
+        assert_eq!(wide.clone().pad_right(6).display_width(), 6);
+        assert_eq!(wide.ellipsize(4, "\u{2026}"), "\u{65e5}\u{2026}".red());
+    }
+
+    #[test]
+    fn ut_quantize() {
+        // nearest colors by the CIELAB color difference
+        let reference = [
+            ((0, 0, 0), 16),
+            ((5, 5, 5), 232),
+            ((18, 18, 18), 233),
+            ((95, 95, 95), 59),
+            ((120, 121, 120), 243),
+            ((128, 128, 128), 244),
+            ((245, 245, 245), 255),
+            ((250, 250, 250), 231),
+            ((255, 255, 255), 231),
+            ((255, 136, 0), 208),
+            ((255, 215, 0), 220),
+            ((0, 128, 128), 30),
+            ((34, 139, 34), 28),
+            ((100, 149, 237), 68),
+            ((75, 0, 130), 54),
+            ((102, 51, 153), 54),
+            ((139, 69, 19), 130),
+            ((220, 20, 60), 197),
+            ((255, 192, 203), 217),
+        ];
+        for ((r, g, b), index) in reference {
+            assert_eq!(
+                palette::compute_index(&r, &g, &b),
+                index,
+                "({}, {}, {})",
+                r,
+                g,
+                b
+            );
+        }
+        // every color of the cube and greyscale ramp maps to itself
+        for i in 16..=255 {
+            let (r, g, b) = palette::index_rgb(i);
+            assert_eq!(palette::compute_index(&r, &g, &b), i);
+        }
     }
 
     #[test]
     fn ut_round() {
-        // assert_eq!(downscale(1), 0); 
+        // assert_eq!(downscale(1), 0);
         // assert_eq!(downscale(15), 0);
         // assert_eq!(downscale(16), 0);
         // assert_eq!(downscale(31), 0);
         // assert_eq!(downscale(32), 0);
-        // assert_eq!(downscale(0), 0); 
+        // assert_eq!(downscale(0), 0);
         // assert_eq!(downscale(95), 1);
         // assert_eq!(downscale(135), 2);
         // assert_eq!(downscale(175), 3);
@@ -896,7 +3142,6 @@ mod tests {
         // println!("{}", ((downscale(255) * 36) + (downscale(175) * 6) + downscale(95)) + 16);
         // println!("{}", ((downscale(255) * 36) + (downscale(255) * 6) + downscale(255)) + 16);
 
-
         // for i in 0..24 {
         //     println!("{} = {}", (i*10) + 8, downscale_grey((i*10)+ 8));
         // }
@@ -0,0 +1,293 @@
+use super::ansi::{Token, Tokens};
+use super::palette::*;
+use super::{Code, ColoredStr, StyledText};
+
+/// Parses text containing ANSI escape sequences into a [StyledText].
+///
+/// SGR sequences set the [Code] of the text that follows them, including
+/// 16-color, 256-color and truecolor values and attributes being turned on and
+/// off. OSC 8 hyperlinks become links on their spans. Any other escape
+/// sequence is skipped.
+///
+/// ```
+/// use crayon::{parse_ansi, Color, ColoredStr};
+///
+/// let text = parse_ansi("\u{1b}[1;31merror\u{1b}[0m: not found");
+/// assert_eq!(text.spans().next(), Some(ColoredStr::new("error").red().bold()));
+/// ```
+pub fn parse_ansi(s: &str) -> StyledText {
+    let mut text = StyledText::new();
+    let mut code = Code::new();
+    let mut link = None;
+    for token in Tokens::new(s) {
+        match token {
+            Token::Text(data) => text.push(ColoredStr { data, code, link }),
+            Token::Csi {
+                params,
+                action: 'm',
+            } if is_sgr(params) => apply_sgr(&mut code, params),
+            Token::Osc(payload) => {
+                if let Some((_, url)) = payload.strip_prefix("8;").and_then(|p| p.split_once(';')) {
+                    link = Some(url).filter(|url| !url.is_empty());
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Parses the escape sequences embedded in the spans of `text`, such as text
+/// formatted from colored strings, layering what they set over the codes and
+/// links of the spans they are in.
+pub(crate) fn parse_nested(text: &StyledText) -> StyledText {
+    let mut parsed = StyledText::new();
+    for span in text.spans() {
+        for inner in parse_ansi(span.data).spans() {
+            parsed.push(ColoredStr {
+                code: span.code.overlay(&inner.code),
+                link: inner.link.or(span.link),
+                ..inner
+            });
+        }
+    }
+    parsed
+}
+
+/// Checks that the parameters hold no private markers or intermediate bytes.
+pub(crate) fn is_sgr(params: &str) -> bool {
+    params
+        .bytes()
+        .all(|b| b.is_ascii_digit() || b == b';' || b == b':')
+}
+
+/// A color given by the parameters following `38`, `48` or `58`.
+enum Extended {
+    Index(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Applies the parameters of an SGR sequence to `code` in order.
+///
+/// Unknown or malformed parameters are ignored.
+pub(crate) fn apply_sgr(code: &mut Code, params: &str) {
+    let mut params = params.split(';');
+    while let Some(param) = params.next() {
+        let (n, sub) = match param.split_once(':') {
+            Some((n, sub)) => (n, Some(sub)),
+            None => (param, None),
+        };
+        let n = match n {
+            "" => 0,
+            n => match n.parse::<u16>() {
+                Ok(n) => n,
+                Err(_) => continue,
+            },
+        };
+        match n {
+            0 => *code = Code::new(),
+            1 => code.bold = Some(Bold),
+            2 => code.dim = Some(Dim),
+            3 => code.italic = Some(Italic),
+            4 => {
+                code.underline = match sub {
+                    None | Some("1") => Some(Underline::Single),
+                    Some("2") => Some(Underline::Double),
+                    Some("3") => Some(Underline::Curly),
+                    Some("4") => Some(Underline::Dotted),
+                    Some("5") => Some(Underline::Dashed),
+                    Some("0") => None,
+                    Some(_) => code.underline,
+                }
+            }
+            5 | 6 => code.blink = Some(Blink),
+            7 => code.reversed = Some(Reversed),
+            8 => code.hidden = Some(Hidden),
+            9 => code.strikethrough = Some(Strikethrough),
+            21 => code.underline = Some(Underline::Double),
+            22 => {
+                code.bold = None;
+                code.dim = None;
+            }
+            23 => code.italic = None,
+            24 => code.underline = None,
+            25 => code.blink = None,
+            27 => code.reversed = None,
+            28 => code.hidden = None,
+            29 => code.strikethrough = None,
+            30..=37 => code.fg = Some(Fg::from_offset(n as u8 - 30)),
+            39 => code.fg = None,
+            40..=47 => code.bg = Some(Bg::from_offset(n as u8 - 40)),
+            49 => code.bg = None,
+            53 => code.overline = Some(Overline),
+            55 => code.overline = None,
+            59 => code.underline_color = None,
+            90..=97 => code.fg = Some(Fg::from_offset(n as u8 - 90 + 8)),
+            100..=107 => code.bg = Some(Bg::from_offset(n as u8 - 100 + 8)),
+            38 | 48 | 58 => {
+                let color = match sub {
+                    Some(sub) => extended_colon(sub),
+                    None => extended_semicolon(&mut params),
+                };
+                match (n, color) {
+                    (38, Some(Extended::Index(i))) => code.fg = Some(Fg::Index(i)),
+                    (38, Some(Extended::Rgb(r, g, b))) => code.fg = Some(Fg::Rgb(r, g, b)),
+                    (48, Some(Extended::Index(i))) => code.bg = Some(Bg::Index(i)),
+                    (48, Some(Extended::Rgb(r, g, b))) => code.bg = Some(Bg::Rgb(r, g, b)),
+                    (58, Some(Extended::Index(i))) => {
+                        code.underline_color = Some(UnderlineColor::Index(i))
+                    }
+                    (58, Some(Extended::Rgb(r, g, b))) => {
+                        code.underline_color = Some(UnderlineColor::Rgb(r, g, b))
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Reads an extended color from colon-separated sub-parameters such as
+/// `5:208`, `2:255:136:0` or `2::255:136:0` with an empty color space.
+fn extended_colon(sub: &str) -> Option<Extended> {
+    let args: Vec<&str> = sub.split(':').collect();
+    match args.as_slice() {
+        ["5", i] => Some(Extended::Index(i.parse().ok()?)),
+        ["2", _, r, g, b] | ["2", r, g, b] => Some(Extended::Rgb(
+            r.parse().ok()?,
+            g.parse().ok()?,
+            b.parse().ok()?,
+        )),
+        _ => None,
+    }
+}
+
+/// Reads an extended color from the parameters following it, such as `5;208`
+/// or `2;255;136;0`, consuming them even when they are malformed.
+fn extended_semicolon<'a>(params: &mut impl Iterator<Item = &'a str>) -> Option<Extended> {
+    match params.next()? {
+        "5" => Some(Extended::Index(params.next()?.parse().ok()?)),
+        "2" => {
+            let (r, g, b) = (params.next()?, params.next()?, params.next()?);
+            Some(Extended::Rgb(
+                r.parse().ok()?,
+                g.parse().ok()?,
+                b.parse().ok()?,
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{override_color, with_color_level, Color, ColorLevel, Stream};
+
+    fn spans(s: &str) -> Vec<(String, Code)> {
+        parse_ansi(s)
+            .spans()
+            .map(|span| (span.data.to_string(), span.code))
+            .collect()
+    }
+
+    #[test]
+    fn ut_parse_colors() {
+        assert_eq!(
+            parse_ansi("a\u{1b}[31mb\u{1b}[39mc"),
+            StyledText::from("a") + ColoredStr::new("b").red() + "c"
+        );
+        assert_eq!(
+            spans("\u{1b}[91;104mx\u{1b}[38;5;208;48;2;1;2;3my\u{1b}[38:2::4:5:6;48:5:17mz"),
+            vec![
+                (
+                    "x".to_string(),
+                    Code {
+                        fg: Some(Fg::BrightRed),
+                        bg: Some(Bg::BrightBlue),
+                        ..Code::new()
+                    }
+                ),
+                (
+                    "y".to_string(),
+                    Code {
+                        fg: Some(Fg::Index(208)),
+                        bg: Some(Bg::Rgb(1, 2, 3)),
+                        ..Code::new()
+                    }
+                ),
+                (
+                    "z".to_string(),
+                    Code {
+                        fg: Some(Fg::Rgb(4, 5, 6)),
+                        bg: Some(Bg::Index(17)),
+                        ..Code::new()
+                    }
+                ),
+            ]
+        );
+        // malformed colors are skipped along with their parameters
+        assert_eq!(
+            spans("\u{1b}[38;5;300;1mx"),
+            vec![(
+                "x".to_string(),
+                Code {
+                    bold: Some(Bold),
+                    ..Code::new()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn ut_parse_attributes() {
+        let text =
+            parse_ansi("\u{1b}[1;2;3;4:3;58;5;9ma\u{1b}[22;24mb\u{1b}[4:0;59;9;53mc\u{1b}[mdone");
+        assert_eq!(
+            text.spans().collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("a")
+                    .bold()
+                    .dim()
+                    .italic()
+                    .curly_underline()
+                    .underline_wheel(9),
+                ColoredStr::new("b").italic().underline_wheel(9),
+                ColoredStr::new("c").italic().strikethrough().overline(),
+                ColoredStr::new("done"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ut_parse_skips_unknown() {
+        // cursor movement, private modes, titles and charset switches
+        let text = parse_ansi(
+            "\u{1b}[2K\u{1b}[?25l\u{1b}]0;title\u{7}\u{1b}(B\u{1b}[>4;2mplain\u{1b}[1Ftext",
+        );
+        assert_eq!(text, StyledText::from("plaintext"));
+        let text = parse_ansi("\u{1b}]8;;https://example.com\u{1b}\\here\u{1b}]8;;\u{1b}\\ and");
+        assert_eq!(
+            text.spans().map(|s| s.link).collect::<Vec<Option<&str>>>(),
+            vec![Some("https://example.com"), None]
+        );
+        assert_eq!(text.get_data(), "here and");
+    }
+
+    #[test]
+    fn ut_parse_round_trip() {
+        let text = "error".bright_red().bold()
+            + ": "
+            + "file.rs"
+                .rgb(255, 136, 0)
+                .dashed_underline()
+                .link("file.rs")
+            + " not found".bg_wheel(17).reversed().dim();
+        let _color = override_color(true);
+        let rendered = with_color_level(ColorLevel::TrueColor, || {
+            text.for_stream(Stream::Stdout).to_string()
+        });
+        assert_eq!(parse_ansi(&rendered), text);
+    }
+}
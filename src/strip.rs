@@ -0,0 +1,178 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use super::ansi::{Action, Scanner, Token, Tokens};
+
+/// Removes all ANSI escape sequences from `s`, including CSI sequences such as
+/// SGR codes, OSC sequences such as OSC 8 hyperlinks, and any other escapes.
+///
+/// Borrows `s` when it contains no escape sequences.
+///
+/// ```
+/// use crayon::{strip_ansi, Color};
+///
+/// let colored = "error".red().bold().link("https://example.com").to_string();
+/// assert_eq!(strip_ansi(&colored), "error");
+/// ```
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    let mut tokens = Tokens::new(s);
+    match (tokens.next(), tokens.clone().next()) {
+        (None, _) => Cow::Borrowed(s),
+        (Some(Token::Text(text)), None) => Cow::Borrowed(text),
+        (first, _) => Cow::Owned(
+            first
+                .into_iter()
+                .chain(tokens)
+                .filter_map(|token| match token {
+                    Token::Text(text) => Some(text),
+                    _ => None,
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// A writer that removes ANSI escape sequences from everything written to it
+/// before passing it on to the inner writer.
+///
+/// Escape sequences may be split across separate writes.
+///
+/// ```
+/// use std::io::Write;
+/// use crayon::StripAnsi;
+///
+/// let mut writer = StripAnsi::new(Vec::new());
+/// writer.write_all(b"\x1b[3").unwrap();
+/// writer.write_all(b"1mred\x1b[39m").unwrap();
+/// assert_eq!(writer.into_inner(), b"red");
+/// ```
+#[derive(Debug)]
+pub struct StripAnsi<W: Write> {
+    inner: W,
+    scanner: Scanner,
+}
+
+impl<W: Write> StripAnsi<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            scanner: Scanner::new(),
+        }
+    }
+
+    /// References the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Mutably references the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwraps the inner writer, dropping any incomplete escape sequence.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // start of the run of text not yet written
+        let mut run = None;
+        for (i, &byte) in buf.iter().enumerate() {
+            let mut action = self.scanner.feed(byte);
+            match action {
+                Action::Abort => action = self.scanner.feed(byte),
+                Action::Release => {
+                    match i {
+                        0 => self.inner.write_all(&[0xc2])?,
+                        _ => {
+                            run.get_or_insert(i - 1);
+                        }
+                    }
+                    action = self.scanner.feed(byte);
+                }
+                _ => {}
+            }
+            match action {
+                Action::Text => {
+                    run.get_or_insert(i);
+                }
+                // the held byte came at the end of a previous write
+                Action::HeldText if i == 0 => {
+                    self.inner.write_all(&[0xc2])?;
+                    run = Some(i);
+                }
+                Action::HeldText => {
+                    run.get_or_insert(i - 1);
+                }
+                _ => {
+                    if let Some(start) = run.take() {
+                        self.inner.write_all(&buf[start..i])?;
+                    }
+                }
+            }
+        }
+        if let Some(start) = run {
+            self.inner.write_all(&buf[start..])?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{override_color, override_color_level, Color, ColorLevel};
+
+    #[test]
+    fn ut_strip_ansi() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        assert!(matches!(strip_ansi("plain"), Cow::Borrowed("plain")));
+        assert!(matches!(strip_ansi(""), Cow::Borrowed("")));
+        let _color = override_color(true);
+        let text = "error".red().bold() + ": " + "file.rs".underline().link("file.rs");
+        assert_eq!(strip_ansi(&text.to_string()), "error: file.rs");
+        assert_eq!(
+            strip_ansi("\u{1b}[2K\u{1b}]0;title\u{7}a\u{1b}(Bb\u{9b}1mc\u{1b}Pdata\u{1b}\\d"),
+            "abcd"
+        );
+        // an escape at the end is dropped even when incomplete
+        assert_eq!(strip_ansi("x\u{1b}[31"), "x");
+        assert_eq!(strip_ansi("\u{a0}\u{1b}[m\u{e9}"), "\u{a0}\u{e9}");
+    }
+
+    #[test]
+    fn ut_strip_writer() {
+        let input =
+            "a\u{1b}[31mb\u{1b}]8;;url\u{1b}\\c\u{a0}\u{1b}[39;\u{e9}\u{9b}0md\u{9d}x\u{9c}e";
+        // whole, then split across every possible pair of writes
+        for split in 0..=input.len() {
+            let mut writer = StripAnsi::new(Vec::new());
+            writer.write_all(&input.as_bytes()[..split]).unwrap();
+            writer.write_all(&input.as_bytes()[split..]).unwrap();
+            writer.flush().unwrap();
+            assert_eq!(
+                String::from_utf8(writer.into_inner()).unwrap(),
+                strip_ansi(input)
+            );
+        }
+        let mut writer = StripAnsi::new(Vec::new());
+        input.bytes().for_each(|b| writer.write_all(&[b]).unwrap());
+        assert_eq!(writer.get_ref(), b"abc\xc2\xa0\xc3\xa9de");
+        // a held byte that turns out not to start a C1 control is kept as text
+        // and the byte after it is scanned anew
+        let input = b"\xc2\x1b[31mred\x1b[39m\xc2\xc2\x9b1mx\xc2y";
+        for split in 0..=input.len() {
+            let mut writer = StripAnsi::new(Vec::new());
+            writer.write_all(&input[..split]).unwrap();
+            writer.write_all(&input[split..]).unwrap();
+            assert_eq!(writer.get_ref(), b"\xc2red\xc2x\xc2y");
+        }
+    }
+}
@@ -0,0 +1,638 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::palette::{Bg, Fg};
+
+/// A 24-bit color made of red, green and blue components.
+///
+/// Parses from a hex color such as `#ff8800` or `#f80`, from `rgb(255, 136, 0)`
+/// or from a CSS color name such as `rebeccapurple`.
+///
+/// ```
+/// use crayon::Rgb;
+///
+/// assert_eq!("#f80".parse(), Ok(Rgb::new(255, 136, 0)));
+/// assert_eq!(Rgb::new(102, 51, 153).to_string(), "#663399");
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Creates a color from a hue in degrees and a saturation and lightness
+    /// from 0 to 1.
+    ///
+    /// ```
+    /// use crayon::{Color, Rgb};
+    ///
+    /// // status colors from green through yellow to red as severity rises
+    /// let status = |severity: f64| Rgb::from_hsl(120.0 * (1.0 - severity), 0.8, 0.45);
+    /// assert_eq!(status(0.0), Rgb::new(23, 207, 23));
+    /// assert_eq!(status(1.0), Rgb::new(207, 23, 23));
+    /// println!("{}", "disk 90% full".fg(status(0.9).into()));
+    /// ```
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Self::from_chroma(h, chroma, l - chroma / 2.0)
+    }
+
+    /// Creates a color from a hue in degrees and a saturation and value from
+    /// 0 to 1.
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let chroma = v * s;
+        Self::from_chroma(h, chroma, v - chroma)
+    }
+
+    /// Creates a color from a hue, a chroma and the amount `m` added to every
+    /// component.
+    fn from_chroma(h: f64, chroma: f64, m: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::new(channel(r + m), channel(g + m), channel(b + m))
+    }
+
+    /// Gets the hue in degrees, the chroma and the largest and smallest
+    /// components from 0 to 1.
+    fn hue_chroma(self) -> (f64, f64, f64, f64) {
+        let (r, g, b) = (
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0,
+        );
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        (hue, chroma, max, min)
+    }
+
+    /// Converts the color into a hue in degrees and a saturation and lightness
+    /// from 0 to 1.
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let (h, chroma, max, min) = self.hue_chroma();
+        let l = (max + min) / 2.0;
+        let s = match chroma {
+            0.0 => 0.0,
+            _ => chroma / (1.0 - (2.0 * l - 1.0).abs()),
+        };
+        (h, s, l)
+    }
+
+    /// Converts the color into a hue in degrees and a saturation and value
+    /// from 0 to 1.
+    pub fn to_hsv(self) -> (f64, f64, f64) {
+        let (h, chroma, max, _) = self.hue_chroma();
+        let s = match chroma {
+            0.0 => 0.0,
+            _ => chroma / max,
+        };
+        (h, s, max)
+    }
+
+    /// Raises the HSL lightness by `amount`, up to white at 1.
+    pub fn lighten(self, amount: f64) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s, l + amount)
+    }
+
+    /// Lowers the HSL lightness by `amount`, down to black at 0.
+    pub fn darken(self, amount: f64) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// Raises the HSL saturation by `amount`, or lowers it towards grey when
+    /// `amount` is negative.
+    pub fn saturate(self, amount: f64) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h, s + amount, l)
+    }
+
+    /// Mixes in `other` by `weight` from 0, which keeps this color, to 1,
+    /// which gives `other`.
+    ///
+    /// ```
+    /// use crayon::Rgb;
+    ///
+    /// let orange = Rgb::new(255, 0, 0).mix(Rgb::new(255, 255, 0), 0.5);
+    /// assert_eq!(orange, Rgb::new(255, 128, 0));
+    /// ```
+    pub fn mix(self, other: Self, weight: f64) -> Self {
+        let weight = weight.clamp(0.0, 1.0);
+        let lerp = |a: u8, b: u8| channel((a as f64 + (b as f64 - a as f64) * weight) / 255.0);
+        Self::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+
+    /// Rotates the hue by 180 degrees, keeping the saturation and lightness.
+    pub fn complement(self) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self::from_hsl(h + 180.0, s, l)
+    }
+}
+
+/// Scales a component from 0 to 1 up to 0 to 255.
+fn channel(c: f64) -> u8 {
+    (c * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+impl Display for Rgb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// A foreground or background color picked from the terminal's 16 standard
+/// and bright colors, its 256-color palette, or any 24-bit color.
+///
+/// Besides everything [Rgb] parses from, it parses from the 8 ANSI color
+/// names such as `red`, their bright variants such as `bright_red` and
+/// 256-color indices such as `ansi(208)`. ANSI names take precedence over the
+/// CSS color names they share so that they follow the terminal's theme.
+///
+/// ```
+/// use crayon::{Rgb, Swatch};
+///
+/// assert_eq!("bright-blue".parse(), Ok(Swatch::Ansi(12)));
+/// assert_eq!("ansi(208)".parse(), Ok(Swatch::Index(208)));
+/// assert_eq!("rebeccapurple".parse(), Ok(Swatch::Rgb(Rgb::new(102, 51, 153))));
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Swatch {
+    /// One of the 8 standard colors by its offset 0-7, or one of their bright
+    /// variants by 8-15.
+    Ansi(u8),
+    /// A color of the 256-color palette.
+    Index(u8),
+    Rgb(Rgb),
+}
+
+impl Swatch {
+    pub(crate) const fn fg(self) -> Fg {
+        match self {
+            Self::Ansi(n) => Fg::from_offset(n),
+            Self::Index(i) => Fg::Index(i),
+            Self::Rgb(c) => Fg::Rgb(c.r, c.g, c.b),
+        }
+    }
+
+    pub(crate) const fn bg(self) -> Bg {
+        match self {
+            Self::Ansi(n) => Bg::from_offset(n),
+            Self::Index(i) => Bg::Index(i),
+            Self::Rgb(c) => Bg::Rgb(c.r, c.g, c.b),
+        }
+    }
+}
+
+impl From<Rgb> for Swatch {
+    fn from(c: Rgb) -> Self {
+        Self::Rgb(c)
+    }
+}
+
+/// Why a color could not be parsed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Reason {
+    Hex,
+    Rgb,
+    Ansi,
+    Unknown,
+}
+
+/// An error returned when parsing an [Rgb] or [Swatch] fails.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseColorError {
+    input: String,
+    reason: Reason,
+}
+
+impl ParseColorError {
+    fn new(input: &str, reason: Reason) -> Self {
+        Self {
+            input: input.to_string(),
+            reason,
+        }
+    }
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.reason {
+            Reason::Hex => write!(
+                f,
+                "invalid hex color `{}`: expected 3 or 6 hexadecimal digits",
+                self.input
+            ),
+            Reason::Rgb => write!(
+                f,
+                "invalid color `{}`: expected three components from 0 to 255",
+                self.input
+            ),
+            Reason::Ansi => write!(
+                f,
+                "invalid color `{}`: expected an index from 0 to 255",
+                self.input
+            ),
+            Reason::Unknown => write!(
+                f,
+                "unknown color `{}`: expected a hex color, rgb(r, g, b), ansi(n) or a color name",
+                self.input
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Lowercases a color name and removes the separators between its words.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Gets the arguments of a call to the function `name` such as `rgb(1, 2, 3)`,
+/// ignoring case.
+fn arguments<'s>(s: &'s str, name: &str) -> Option<&'s str> {
+    let prefix = s.get(..name.len())?;
+    if !prefix.eq_ignore_ascii_case(name) {
+        return None;
+    }
+    s[name.len()..]
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
+fn parse_hex(hex: &str) -> Option<Rgb> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16);
+    match hex.len() {
+        3 => Some(Rgb::new(
+            digit(0, 1).ok()? * 17,
+            digit(1, 1).ok()? * 17,
+            digit(2, 1).ok()? * 17,
+        )),
+        6 => Some(Rgb::new(
+            digit(0, 2).ok()?,
+            digit(1, 2).ok()?,
+            digit(2, 2).ok()?,
+        )),
+        _ => None,
+    }
+}
+
+fn parse_rgb(args: &str) -> Option<Rgb> {
+    let components: Vec<&str> = args
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|c| !c.is_empty())
+        .collect();
+    match components.as_slice() {
+        [r, g, b] => Some(Rgb::new(r.parse().ok()?, g.parse().ok()?, b.parse().ok()?)),
+        _ => None,
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(hex) = trimmed.strip_prefix('#') {
+            parse_hex(hex).ok_or_else(|| ParseColorError::new(s, Reason::Hex))
+        } else if let Some(args) = arguments(trimmed, "rgb") {
+            parse_rgb(args).ok_or_else(|| ParseColorError::new(s, Reason::Rgb))
+        } else {
+            let name = normalize(trimmed);
+            CSS_COLORS
+                .binary_search_by_key(&name.as_str(), |&(css, _)| css)
+                .map(|i| CSS_COLORS[i].1)
+                .map_err(|_| ParseColorError::new(s, Reason::Unknown))
+        }
+    }
+}
+
+// names of the 8 standard ANSI colors by their offset
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+impl FromStr for Swatch {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let name = normalize(trimmed);
+        let (bright, base) = match name.strip_prefix("bright") {
+            Some(base) => (8, base),
+            None => (0, name.as_str()),
+        };
+        if let Some(offset) = ANSI_NAMES.iter().position(|&ansi| ansi == base) {
+            return Ok(Self::Ansi(offset as u8 + bright));
+        }
+        if let Some(args) = arguments(trimmed, "ansi") {
+            return args
+                .trim()
+                .parse()
+                .map(Self::Index)
+                .map_err(|_| ParseColorError::new(s, Reason::Ansi));
+        }
+        trimmed
+            .parse()
+            .map(Self::Rgb)
+            .map_err(|e: ParseColorError| ParseColorError::new(s, e.reason))
+    }
+}
+
+// named colors of CSS Color Module Level 4, sorted by name
+const CSS_COLORS: &[(&str, Rgb)] = &[
+    ("aliceblue", Rgb::new(240, 248, 255)),
+    ("antiquewhite", Rgb::new(250, 235, 215)),
+    ("aqua", Rgb::new(0, 255, 255)),
+    ("aquamarine", Rgb::new(127, 255, 212)),
+    ("azure", Rgb::new(240, 255, 255)),
+    ("beige", Rgb::new(245, 245, 220)),
+    ("bisque", Rgb::new(255, 228, 196)),
+    ("black", Rgb::new(0, 0, 0)),
+    ("blanchedalmond", Rgb::new(255, 235, 205)),
+    ("blue", Rgb::new(0, 0, 255)),
+    ("blueviolet", Rgb::new(138, 43, 226)),
+    ("brown", Rgb::new(165, 42, 42)),
+    ("burlywood", Rgb::new(222, 184, 135)),
+    ("cadetblue", Rgb::new(95, 158, 160)),
+    ("chartreuse", Rgb::new(127, 255, 0)),
+    ("chocolate", Rgb::new(210, 105, 30)),
+    ("coral", Rgb::new(255, 127, 80)),
+    ("cornflowerblue", Rgb::new(100, 149, 237)),
+    ("cornsilk", Rgb::new(255, 248, 220)),
+    ("crimson", Rgb::new(220, 20, 60)),
+    ("cyan", Rgb::new(0, 255, 255)),
+    ("darkblue", Rgb::new(0, 0, 139)),
+    ("darkcyan", Rgb::new(0, 139, 139)),
+    ("darkgoldenrod", Rgb::new(184, 134, 11)),
+    ("darkgray", Rgb::new(169, 169, 169)),
+    ("darkgreen", Rgb::new(0, 100, 0)),
+    ("darkgrey", Rgb::new(169, 169, 169)),
+    ("darkkhaki", Rgb::new(189, 183, 107)),
+    ("darkmagenta", Rgb::new(139, 0, 139)),
+    ("darkolivegreen", Rgb::new(85, 107, 47)),
+    ("darkorange", Rgb::new(255, 140, 0)),
+    ("darkorchid", Rgb::new(153, 50, 204)),
+    ("darkred", Rgb::new(139, 0, 0)),
+    ("darksalmon", Rgb::new(233, 150, 122)),
+    ("darkseagreen", Rgb::new(143, 188, 143)),
+    ("darkslateblue", Rgb::new(72, 61, 139)),
+    ("darkslategray", Rgb::new(47, 79, 79)),
+    ("darkslategrey", Rgb::new(47, 79, 79)),
+    ("darkturquoise", Rgb::new(0, 206, 209)),
+    ("darkviolet", Rgb::new(148, 0, 211)),
+    ("deeppink", Rgb::new(255, 20, 147)),
+    ("deepskyblue", Rgb::new(0, 191, 255)),
+    ("dimgray", Rgb::new(105, 105, 105)),
+    ("dimgrey", Rgb::new(105, 105, 105)),
+    ("dodgerblue", Rgb::new(30, 144, 255)),
+    ("firebrick", Rgb::new(178, 34, 34)),
+    ("floralwhite", Rgb::new(255, 250, 240)),
+    ("forestgreen", Rgb::new(34, 139, 34)),
+    ("fuchsia", Rgb::new(255, 0, 255)),
+    ("gainsboro", Rgb::new(220, 220, 220)),
+    ("ghostwhite", Rgb::new(248, 248, 255)),
+    ("gold", Rgb::new(255, 215, 0)),
+    ("goldenrod", Rgb::new(218, 165, 32)),
+    ("gray", Rgb::new(128, 128, 128)),
+    ("green", Rgb::new(0, 128, 0)),
+    ("greenyellow", Rgb::new(173, 255, 47)),
+    ("grey", Rgb::new(128, 128, 128)),
+    ("honeydew", Rgb::new(240, 255, 240)),
+    ("hotpink", Rgb::new(255, 105, 180)),
+    ("indianred", Rgb::new(205, 92, 92)),
+    ("indigo", Rgb::new(75, 0, 130)),
+    ("ivory", Rgb::new(255, 255, 240)),
+    ("khaki", Rgb::new(240, 230, 140)),
+    ("lavender", Rgb::new(230, 230, 250)),
+    ("lavenderblush", Rgb::new(255, 240, 245)),
+    ("lawngreen", Rgb::new(124, 252, 0)),
+    ("lemonchiffon", Rgb::new(255, 250, 205)),
+    ("lightblue", Rgb::new(173, 216, 230)),
+    ("lightcoral", Rgb::new(240, 128, 128)),
+    ("lightcyan", Rgb::new(224, 255, 255)),
+    ("lightgoldenrodyellow", Rgb::new(250, 250, 210)),
+    ("lightgray", Rgb::new(211, 211, 211)),
+    ("lightgreen", Rgb::new(144, 238, 144)),
+    ("lightgrey", Rgb::new(211, 211, 211)),
+    ("lightpink", Rgb::new(255, 182, 193)),
+    ("lightsalmon", Rgb::new(255, 160, 122)),
+    ("lightseagreen", Rgb::new(32, 178, 170)),
+    ("lightskyblue", Rgb::new(135, 206, 250)),
+    ("lightslategray", Rgb::new(119, 136, 153)),
+    ("lightslategrey", Rgb::new(119, 136, 153)),
+    ("lightsteelblue", Rgb::new(176, 196, 222)),
+    ("lightyellow", Rgb::new(255, 255, 224)),
+    ("lime", Rgb::new(0, 255, 0)),
+    ("limegreen", Rgb::new(50, 205, 50)),
+    ("linen", Rgb::new(250, 240, 230)),
+    ("magenta", Rgb::new(255, 0, 255)),
+    ("maroon", Rgb::new(128, 0, 0)),
+    ("mediumaquamarine", Rgb::new(102, 205, 170)),
+    ("mediumblue", Rgb::new(0, 0, 205)),
+    ("mediumorchid", Rgb::new(186, 85, 211)),
+    ("mediumpurple", Rgb::new(147, 112, 219)),
+    ("mediumseagreen", Rgb::new(60, 179, 113)),
+    ("mediumslateblue", Rgb::new(123, 104, 238)),
+    ("mediumspringgreen", Rgb::new(0, 250, 154)),
+    ("mediumturquoise", Rgb::new(72, 209, 204)),
+    ("mediumvioletred", Rgb::new(199, 21, 133)),
+    ("midnightblue", Rgb::new(25, 25, 112)),
+    ("mintcream", Rgb::new(245, 255, 250)),
+    ("mistyrose", Rgb::new(255, 228, 225)),
+    ("moccasin", Rgb::new(255, 228, 181)),
+    ("navajowhite", Rgb::new(255, 222, 173)),
+    ("navy", Rgb::new(0, 0, 128)),
+    ("oldlace", Rgb::new(253, 245, 230)),
+    ("olive", Rgb::new(128, 128, 0)),
+    ("olivedrab", Rgb::new(107, 142, 35)),
+    ("orange", Rgb::new(255, 165, 0)),
+    ("orangered", Rgb::new(255, 69, 0)),
+    ("orchid", Rgb::new(218, 112, 214)),
+    ("palegoldenrod", Rgb::new(238, 232, 170)),
+    ("palegreen", Rgb::new(152, 251, 152)),
+    ("paleturquoise", Rgb::new(175, 238, 238)),
+    ("palevioletred", Rgb::new(219, 112, 147)),
+    ("papayawhip", Rgb::new(255, 239, 213)),
+    ("peachpuff", Rgb::new(255, 218, 185)),
+    ("peru", Rgb::new(205, 133, 63)),
+    ("pink", Rgb::new(255, 192, 203)),
+    ("plum", Rgb::new(221, 160, 221)),
+    ("powderblue", Rgb::new(176, 224, 230)),
+    ("purple", Rgb::new(128, 0, 128)),
+    ("rebeccapurple", Rgb::new(102, 51, 153)),
+    ("red", Rgb::new(255, 0, 0)),
+    ("rosybrown", Rgb::new(188, 143, 143)),
+    ("royalblue", Rgb::new(65, 105, 225)),
+    ("saddlebrown", Rgb::new(139, 69, 19)),
+    ("salmon", Rgb::new(250, 128, 114)),
+    ("sandybrown", Rgb::new(244, 164, 96)),
+    ("seagreen", Rgb::new(46, 139, 87)),
+    ("seashell", Rgb::new(255, 245, 238)),
+    ("sienna", Rgb::new(160, 82, 45)),
+    ("silver", Rgb::new(192, 192, 192)),
+    ("skyblue", Rgb::new(135, 206, 235)),
+    ("slateblue", Rgb::new(106, 90, 205)),
+    ("slategray", Rgb::new(112, 128, 144)),
+    ("slategrey", Rgb::new(112, 128, 144)),
+    ("snow", Rgb::new(255, 250, 250)),
+    ("springgreen", Rgb::new(0, 255, 127)),
+    ("steelblue", Rgb::new(70, 130, 180)),
+    ("tan", Rgb::new(210, 180, 140)),
+    ("teal", Rgb::new(0, 128, 128)),
+    ("thistle", Rgb::new(216, 191, 216)),
+    ("tomato", Rgb::new(255, 99, 71)),
+    ("turquoise", Rgb::new(64, 224, 208)),
+    ("violet", Rgb::new(238, 130, 238)),
+    ("wheat", Rgb::new(245, 222, 179)),
+    ("white", Rgb::new(255, 255, 255)),
+    ("whitesmoke", Rgb::new(245, 245, 245)),
+    ("yellow", Rgb::new(255, 255, 0)),
+    ("yellowgreen", Rgb::new(154, 205, 50)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_parse_rgb() {
+        assert_eq!("#ff8800".parse(), Ok(Rgb::new(255, 136, 0)));
+        assert_eq!("#F80".parse(), Ok(Rgb::new(255, 136, 0)));
+        assert_eq!(" #123456 ".parse(), Ok(Rgb::new(0x12, 0x34, 0x56)));
+        assert_eq!("rgb(255, 136, 0)".parse(), Ok(Rgb::new(255, 136, 0)));
+        assert_eq!("RGB (1 2 3)".parse(), Ok(Rgb::new(1, 2, 3)));
+        assert_eq!("rebeccapurple".parse(), Ok(Rgb::new(102, 51, 153)));
+        assert_eq!(
+            "Light Goldenrod Yellow".parse(),
+            Ok(Rgb::new(250, 250, 210))
+        );
+        // CSS names keep their CSS meaning as RGB colors
+        assert_eq!("red".parse(), Ok(Rgb::new(255, 0, 0)));
+        assert!(CSS_COLORS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(
+            Rgb::new(255, 136, 0).to_string().parse(),
+            Ok(Rgb::new(255, 136, 0))
+        );
+    }
+
+    #[test]
+    fn ut_hsl() {
+        assert_eq!(Rgb::from_hsl(0.0, 1.0, 0.5), Rgb::new(255, 0, 0));
+        assert_eq!(Rgb::from_hsl(120.0, 1.0, 0.25), Rgb::new(0, 128, 0));
+        assert_eq!(Rgb::from_hsl(-120.0, 1.0, 0.5), Rgb::new(0, 0, 255));
+        assert_eq!(Rgb::from_hsl(270.0, 0.5, 0.4), Rgb::new(102, 51, 153));
+        assert_eq!(Rgb::from_hsl(42.0, 0.0, 2.0), Rgb::new(255, 255, 255));
+        let (h, s, l) = Rgb::new(102, 51, 153).to_hsl();
+        assert_eq!(h, 270.0);
+        assert!((s - 0.5).abs() < 1e-9 && (l - 0.4).abs() < 1e-9);
+        assert_eq!(Rgb::new(128, 128, 128).to_hsl().1, 0.0);
+        assert_eq!(Rgb::new(0, 0, 0).to_hsl(), (0.0, 0.0, 0.0));
+        assert_eq!(Rgb::from_hsv(60.0, 1.0, 1.0), Rgb::new(255, 255, 0));
+        assert_eq!(Rgb::from_hsv(200.0, 0.5, 0.8), Rgb::new(102, 170, 204));
+        assert_eq!(Rgb::new(255, 255, 0).to_hsv(), (60.0, 1.0, 1.0));
+        // every color survives a round trip through both spaces
+        for c in CSS_COLORS.iter().map(|&(_, c)| c) {
+            let (h, s, l) = c.to_hsl();
+            assert_eq!(Rgb::from_hsl(h, s, l), c);
+            let (h, s, v) = c.to_hsv();
+            assert_eq!(Rgb::from_hsv(h, s, v), c);
+        }
+    }
+
+    #[test]
+    fn ut_adjust() {
+        let red = Rgb::new(255, 0, 0);
+        assert_eq!(red.lighten(0.25), Rgb::new(255, 128, 128));
+        assert_eq!(red.darken(0.25), Rgb::new(128, 0, 0));
+        assert_eq!(red.lighten(1.0), Rgb::new(255, 255, 255));
+        assert_eq!(red.darken(1.0), Rgb::new(0, 0, 0));
+        assert_eq!(red.saturate(-1.0), Rgb::new(128, 128, 128));
+        assert_eq!(Rgb::new(153, 102, 102).saturate(0.2), Rgb::new(179, 77, 77));
+        assert_eq!(red.complement(), Rgb::new(0, 255, 255));
+        assert_eq!(Rgb::new(102, 51, 153).complement(), Rgb::new(102, 153, 51));
+        assert_eq!(red.mix(Rgb::new(0, 0, 255), 0.0), red);
+        assert_eq!(red.mix(Rgb::new(0, 0, 255), 0.25), Rgb::new(191, 0, 64));
+        assert_eq!(red.mix(Rgb::new(0, 0, 255), 2.0), Rgb::new(0, 0, 255));
+    }
+
+    #[test]
+    fn ut_parse_swatch() {
+        assert_eq!("red".parse(), Ok(Swatch::Ansi(1)));
+        assert_eq!("Bright_White".parse(), Ok(Swatch::Ansi(15)));
+        assert_eq!("bright black".parse(), Ok(Swatch::Ansi(8)));
+        assert_eq!("ansi(208)".parse(), Ok(Swatch::Index(208)));
+        assert_eq!("#f80".parse(), Ok(Swatch::Rgb(Rgb::new(255, 136, 0))));
+        assert_eq!("orange".parse(), Ok(Swatch::Rgb(Rgb::new(255, 165, 0))));
+        assert_eq!(
+            Swatch::from(Rgb::new(1, 2, 3)),
+            Swatch::Rgb(Rgb::new(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn ut_parse_errors() {
+        let error = |s: &str| s.parse::<Swatch>().unwrap_err().to_string();
+        assert_eq!(
+            error("#ff88"),
+            "invalid hex color `#ff88`: expected 3 or 6 hexadecimal digits"
+        );
+        assert_eq!(
+            error("#gg0000"),
+            "invalid hex color `#gg0000`: expected 3 or 6 hexadecimal digits"
+        );
+        assert_eq!(
+            error("rgb(256, 0, 0)"),
+            "invalid color `rgb(256, 0, 0)`: expected three components from 0 to 255"
+        );
+        assert_eq!(
+            error("rgb(1, 2)"),
+            "invalid color `rgb(1, 2)`: expected three components from 0 to 255"
+        );
+        assert_eq!(
+            error("ansi(300)"),
+            "invalid color `ansi(300)`: expected an index from 0 to 255"
+        );
+        assert_eq!(
+            error("blurple"),
+            "unknown color `blurple`: expected a hex color, rgb(r, g, b), ansi(n) or a color name"
+        );
+        assert!("".parse::<Rgb>().is_err());
+        assert!("ansi(1)".parse::<Rgb>().is_err());
+    }
+}
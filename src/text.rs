@@ -0,0 +1,552 @@
+use std::fmt::Display;
+use std::ops::{Add, AddAssign};
+
+use super::{
+    fit, width, AsAnsi, Code, ColorLevel, ColoredStr, ColoredString, Gradient, OscUrl, Rendered,
+    Stream, Style, ESC_SEQ,
+};
+
+/// A contiguous run of text sharing the same ANSI terminal codes.
+#[derive(Debug, PartialEq, Clone)]
+struct Span {
+    /// Byte offset into the data where the span ends.
+    end: usize,
+    code: Code,
+    link: Option<String>,
+}
+
+/// Text made of multiple spans, each with its own ANSI terminal [Code].
+///
+/// Anything convertible into a `StyledText` can be pushed or added onto it,
+/// including a [ColoredString], a [ColoredStr] and plain strings.
+///
+/// ```
+/// use crayon::{Color, StyledText};
+///
+/// let line = "error".red().bold() + ": " + "file.rs:12".underline() + " not found";
+/// assert_eq!(line.spans().count(), 4);
+///
+/// let mut text = StyledText::new();
+/// text.push("ok".green());
+/// text += " done";
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StyledText {
+    data: String,
+    spans: Vec<Span>,
+}
+
+impl StyledText {
+    pub fn new() -> Self {
+        Self {
+            data: String::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Appends `text` as one or more spans at the end.
+    pub fn push<T: Into<StyledText>>(&mut self, text: T) {
+        let text: StyledText = text.into();
+        let offset = self.data.len();
+        self.data.push_str(&text.data);
+        for span in text.spans {
+            self.push_span(offset + span.end, span.code, span.link);
+        }
+    }
+
+    /// Appends a span ending at `end`, merging it into the last span when they
+    /// share the same codes.
+    fn push_span(&mut self, end: usize, code: Code, link: Option<String>) {
+        if end == self.spans.last().map_or(0, |last| last.end) {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.code == code && last.link == link => last.end = end,
+            _ => self.spans.push(Span { end, code, link }),
+        }
+    }
+
+    /// Iterates over the spans in order as borrowed [ColoredStr] values.
+    pub fn spans(&self) -> impl Iterator<Item = ColoredStr<'_>> {
+        let mut start = 0;
+        self.spans.iter().map(move |span| {
+            let data = &self.data[start..span.end];
+            start = span.end;
+            ColoredStr {
+                data,
+                code: span.code,
+                link: span.link.as_deref(),
+            }
+        })
+    }
+
+    /// Copies the part of the text from byte offset `start` to `end` along
+    /// with the codes of its spans.
+    pub(crate) fn slice(&self, start: usize, end: usize) -> StyledText {
+        let mut text = StyledText::new();
+        let mut offset = 0;
+        for span in self.spans() {
+            let (from, to) = (start.max(offset), end.min(offset + span.data.len()));
+            if from < to {
+                text.push(ColoredStr {
+                    data: &self.data[from..to],
+                    ..span
+                });
+            }
+            offset += span.data.len();
+        }
+        text
+    }
+
+    /// Checks if the text has no contents.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// References the plain text of every span without ANSI codes.
+    pub fn get_data(&self) -> &str {
+        &self.data
+    }
+
+    /// Counts the terminal columns the text takes up, as
+    /// [AsAnsi::display_width] does.
+    pub fn display_width(&self) -> usize {
+        width::str_width(&self.data)
+    }
+
+    /// Formats the text using the color policy of `stream` rather than stdout.
+    pub fn for_stream(&self, stream: Stream) -> Rendered<'_> {
+        Rendered::text(self, stream)
+    }
+
+    /// Applies the style produced by `edit` as an outer style that each span
+    /// inherits for the attributes it leaves unset.
+    pub(crate) fn restyled(mut self, edit: impl FnOnce(Style) -> Style) -> Self {
+        let outer = edit(Style::new()).code;
+        self.spans
+            .iter_mut()
+            .for_each(|span| span.code = outer.overlay(&span.code));
+        self
+    }
+
+    /// Turns every span that is not already linked into a terminal hyperlink
+    /// to `url` using OSC 8.
+    pub fn link(mut self, url: &str) -> Self {
+        self.spans
+            .iter_mut()
+            .filter(|span| span.link.is_none())
+            .for_each(|span| span.link = Some(url.to_string()));
+        self
+    }
+
+    /// Converts the colors of every span to the closest ones representable at
+    /// `level`, as [ColoredString::downgrade] does.
+    pub fn downgrade(mut self, level: ColorLevel) -> Self {
+        self.spans
+            .iter_mut()
+            .for_each(|span| span.code = span.code.downgrade(level));
+        self
+    }
+
+    /// Colors each grapheme cluster along a [Gradient] as an outer color,
+    /// which spans that set their own foreground or background keep.
+    pub fn gradient<'g>(self, gradient: impl Into<Gradient<'g>>) -> Self {
+        gradient.into().apply(&self)
+    }
+
+    /// Cuts the text down to at most `width` terminal columns without
+    /// splitting a grapheme cluster, keeping the codes of every span left.
+    pub fn truncate(mut self, width: usize) -> Self {
+        let end = width::truncate(&self.data, width).0.len();
+        self.cut(end);
+        self
+    }
+
+    /// Pads the text with unstyled spaces on the left up to `width` terminal
+    /// columns.
+    pub fn pad_left(self, width: usize) -> Self {
+        let padding = width.saturating_sub(self.display_width());
+        StyledText::from(" ".repeat(padding)) + self
+    }
+
+    /// Pads the text with unstyled spaces on the right up to `width` terminal
+    /// columns.
+    pub fn pad_right(mut self, width: usize) -> Self {
+        let padding = width.saturating_sub(self.display_width());
+        self.push(" ".repeat(padding));
+        self
+    }
+
+    /// Pads the text with unstyled spaces on both sides up to `width`
+    /// terminal columns, with any odd space on the right.
+    pub fn pad_center(self, width: usize) -> Self {
+        let padding = width.saturating_sub(self.display_width());
+        self.pad_left(width - (padding - padding / 2))
+            .pad_right(width)
+    }
+
+    /// Cuts the text down to at most `width` terminal columns like
+    /// [StyledText::truncate], ending it with `ellipsis` when it does not fit.
+    ///
+    /// The ellipsis takes the codes of the span it replaces the end of.
+    pub fn ellipsize(mut self, width: usize, ellipsis: &str) -> Self {
+        if self.display_width() <= width {
+            return self;
+        }
+        let (ellipsis, columns) = width::truncate(ellipsis, width);
+        let end = width::truncate(&self.data, width - columns).0.len();
+        let cut = self.spans.iter().find(|span| span.end > end).cloned();
+        self.cut(end);
+        if let Some(span) = cut {
+            self.data.push_str(ellipsis);
+            self.push_span(self.data.len(), span.code, span.link);
+        }
+        self
+    }
+
+    /// Drops the text from byte offset `end` on along with its spans.
+    fn cut(&mut self, end: usize) {
+        self.data.truncate(end);
+        let kept = self
+            .spans
+            .iter()
+            .position(|span| span.end >= end)
+            .map_or(self.spans.len(), |i| i + 1);
+        self.spans.truncate(kept);
+        match self.spans.last_mut() {
+            _ if end == 0 => self.spans.clear(),
+            Some(last) => last.end = end,
+            None => {}
+        }
+    }
+
+    /// Writes the text with its ANSI codes converted for `level`.
+    ///
+    /// Only the attributes that change between spans are written, so an inner
+    /// span restores the codes of the text around it when it ends. The text is
+    /// truncated and padded in columns to the precision, width, fill and
+    /// alignment of the formatter, with the padding left unstyled.
+    pub(crate) fn write_ansi(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        level: ColorLevel,
+    ) -> std::fmt::Result {
+        let (data, padding) = fit(&self.data, f);
+        padding.write_before(f)?;
+        if level == ColorLevel::None {
+            f.write_str(data)?;
+            return padding.write_after(f);
+        }
+        let mut code = Code::new();
+        let mut link = None;
+        let mut start = 0;
+        for span in self.spans() {
+            if start == data.len() {
+                break;
+            }
+            let end = data.len().min(start + span.data.len());
+            if span.link != link {
+                if link.is_some() {
+                    write!(f, "{}]8;;{}\\", ESC_SEQ, ESC_SEQ)?;
+                }
+                if let Some(url) = span.link {
+                    write!(f, "{}]8;;{}{}\\", ESC_SEQ, OscUrl(url), ESC_SEQ)?;
+                }
+                link = span.link;
+            }
+            let next = span.code.downgrade(level);
+            code.write_transition(&next, f)?;
+            next.write_nested(&data[start..end], level, f)?;
+            code = next;
+            start = end;
+        }
+        code.write_transition(&Code::new(), f)?;
+        if link.is_some() {
+            write!(f, "{}]8;;{}\\", ESC_SEQ, ESC_SEQ)?;
+        }
+        padding.write_after(f)
+    }
+}
+
+impl Display for StyledText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_ansi(f, Stream::Stdout.color_level())
+    }
+}
+
+impl AsAnsi for StyledText {
+    /// A `StyledText` has no single [Code] for all of its contents.
+    fn as_code(&self) -> Option<&Code> {
+        None
+    }
+
+    fn get_data(&self) -> &str {
+        StyledText::get_data(self)
+    }
+
+    fn display_width(&self) -> usize {
+        StyledText::display_width(self)
+    }
+}
+
+impl From<ColoredStr<'_>> for StyledText {
+    fn from(s: ColoredStr<'_>) -> Self {
+        let mut text = Self::new();
+        text.data.push_str(s.data);
+        text.push_span(s.data.len(), s.code, s.link.map(String::from));
+        text
+    }
+}
+
+impl From<ColoredString> for StyledText {
+    fn from(s: ColoredString) -> Self {
+        let mut text = Self::new();
+        text.push_span(s.data.len(), s.code, s.link);
+        text.data = s.data;
+        text
+    }
+}
+
+impl From<&str> for StyledText {
+    fn from(s: &str) -> Self {
+        Self::from(ColoredStr::new(s))
+    }
+}
+
+impl From<String> for StyledText {
+    fn from(s: String) -> Self {
+        Self::from(ColoredString::from(s))
+    }
+}
+
+impl<T: Into<StyledText>> Add<T> for StyledText {
+    type Output = StyledText;
+
+    fn add(mut self, rhs: T) -> Self::Output {
+        self.push(rhs);
+        self
+    }
+}
+
+impl<T: Into<StyledText>> Add<T> for ColoredString {
+    type Output = StyledText;
+
+    fn add(self, rhs: T) -> Self::Output {
+        StyledText::from(self) + rhs
+    }
+}
+
+impl<T: Into<StyledText>> Add<T> for ColoredStr<'_> {
+    type Output = StyledText;
+
+    fn add(self, rhs: T) -> Self::Output {
+        StyledText::from(self) + rhs
+    }
+}
+
+impl<T: Into<StyledText>> AddAssign<T> for StyledText {
+    fn add_assign(&mut self, rhs: T) {
+        self.push(rhs);
+    }
+}
+
+impl<T: Into<StyledText>> Extend<T> for StyledText {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|text| self.push(text));
+    }
+}
+
+impl<T: Into<StyledText>> FromIterator<T> for StyledText {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut text = Self::new();
+        text.extend(iter);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{override_color_level, with_color, AsAnsi, Color, ColorLevel};
+
+    #[test]
+    fn ut_push_spans() {
+        let mut text = StyledText::new();
+        assert!(text.is_empty());
+        text.push("error".red());
+        text.push(": ");
+        text.push(String::from("file.rs"));
+        text.push(ColoredStr::new(" not found").yellow());
+        assert_eq!(text.get_data(), "error: file.rs not found");
+        assert_eq!(text.as_code(), None);
+        // plain strings next to each other share a span
+        assert_eq!(
+            text.spans().collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("error").red(),
+                ColoredStr::new(": file.rs"),
+                ColoredStr::new(" not found").yellow(),
+            ]
+        );
+        // empty text never creates a span
+        assert_eq!(StyledText::from("".red()).spans().count(), 0);
+        text.push("");
+        text.push("".bold());
+        assert_eq!(text.spans().count(), 3);
+    }
+
+    #[test]
+    fn ut_concatenate() {
+        let text = "error".red().bold() + ": " + "file.rs:12".underline();
+        let mut other = StyledText::from(" not ");
+        other += "found".link("https://example.com");
+        let text = text + other;
+        assert_eq!(text.get_data(), "error: file.rs:12 not found");
+        assert_eq!(
+            text.spans().map(|s| s.data).collect::<Vec<&str>>(),
+            vec!["error", ": ", "file.rs:12", " not ", "found"]
+        );
+        assert_eq!(
+            text.spans().last().unwrap().as_link(),
+            Some("https://example.com")
+        );
+
+        let collected: StyledText = ["a".green(), "b".green(), "c".blue()].into_iter().collect();
+        assert_eq!(collected.get_data(), "abc");
+        assert_eq!(collected.spans().count(), 2);
+
+        let mut extended = StyledText::new();
+        extended.extend(vec!["x", "y"]);
+        assert_eq!(extended, StyledText::from("xy"));
+    }
+
+    #[test]
+    fn ut_nested_inheritance() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let inner = "file.rs".yellow().underline();
+        let line = (StyledText::from("error in ") + inner + " here")
+            .red()
+            .bold();
+        assert_eq!(
+            line.spans().collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("error in ").red().bold(),
+                ColoredStr::new("file.rs").yellow().underline().bold(),
+                ColoredStr::new(" here").red().bold(),
+            ]
+        );
+        with_color(true, || {
+            // the inner span restores the outer codes when it ends
+            assert_eq!(
+                line.to_string(),
+                "\u{1b}[31;1merror in \u{1b}[33;4mfile.rs\u{1b}[31;24m here\u{1b}[39;22m"
+            );
+            // turning off bold also turns off dim, so dim is restored
+            let text = "a".bold().dim() + "b".dim();
+            assert_eq!(text.to_string(), "\u{1b}[1;2ma\u{1b}[22;2mb\u{1b}[22m");
+        });
+        // links only apply to spans without one
+        let text = ("a".link("x") + "b").link("y");
+        assert_eq!(
+            text.spans().map(|s| s.link).collect::<Vec<Option<&str>>>(),
+            vec![Some("x"), Some("y")]
+        );
+    }
+
+    #[test]
+    fn ut_fit_columns() {
+        let text = "error".red() + ": " + "file.rs".underline();
+        assert_eq!(
+            text.clone().truncate(9),
+            "error".red() + ": " + "fi".underline()
+        );
+        assert_eq!(text.clone().truncate(5), StyledText::from("error".red()));
+        assert_eq!(text.clone().truncate(0), StyledText::new());
+        assert_eq!(text.clone().truncate(20), text);
+        assert_eq!(
+            text.clone().pad_left(16),
+            StyledText::from("  ") + text.clone()
+        );
+        assert_eq!(text.clone().pad_right(15), text.clone() + " ");
+        assert_eq!(
+            text.clone().pad_center(17),
+            StyledText::from(" ") + text.clone() + "  "
+        );
+        // the ellipsis takes the codes of the span it cuts into
+        assert_eq!(
+            text.clone().ellipsize(10, "\u{2026}"),
+            "error".red() + ": " + "fi\u{2026}".underline()
+        );
+        assert_eq!(
+            text.clone().ellipsize(6, "\u{2026}"),
+            "error".red() + StyledText::from("\u{2026}")
+        );
+        assert_eq!(
+            text.clone().ellipsize(3, "\u{2026}"),
+            StyledText::from("er\u{2026}".red())
+        );
+        assert_eq!(text.clone().ellipsize(14, "\u{2026}"), text);
+    }
+
+    #[test]
+    fn ut_downgrade() {
+        let text = "a".rgb(255, 0, 0) + "b".bg_wheel(21);
+        assert_eq!(
+            text.downgrade(ColorLevel::Ansi16),
+            "a".bright_red() + "b".bg_blue()
+        );
+    }
+
+    #[test]
+    fn ut_display() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "a".red() + "b" + "c".bold();
+        with_color(true, || {
+            assert_eq!(
+                text.to_string(),
+                "\u{1b}[31ma\u{1b}[39mb\u{1b}[1mc\u{1b}[22m"
+            );
+            assert_eq!(
+                text.for_stream(Stream::Stderr).to_string(),
+                text.to_string()
+            );
+        });
+        with_color(false, || assert_eq!(text.to_string(), "abc"));
+        with_color(true, || {
+            // padding stays outside of the spans
+            assert_eq!(
+                format!("{:>5}|{:-<5}", text, text),
+                "  \u{1b}[31ma\u{1b}[39mb\u{1b}[1mc\u{1b}[22m|\u{1b}[31ma\u{1b}[39mb\u{1b}[1mc\u{1b}[22m--"
+            );
+            // including around a background color
+            assert_eq!(
+                format!("{:>4}", "a".bg_red() + "b".bg_blue()),
+                "  \u{1b}[41ma\u{1b}[44mb\u{1b}[49m"
+            );
+            // truncation can end within a span
+            assert_eq!(format!("{:.2}", text), "\u{1b}[31ma\u{1b}[39mb");
+            assert_eq!(format!("{:.0}", text), "");
+        });
+        with_color(false, || assert_eq!(format!("{:^5.2}", text), " ab  "));
+        // combinators restyle each span instead of flattening the text
+        assert_eq!(
+            text.clone().green(),
+            "a".red() + "b".green() + "c".green().bold()
+        );
+        assert_eq!(
+            text.clone()
+                .bold()
+                .link("x")
+                .spans()
+                .collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("a").red().bold().link("x"),
+                ColoredStr::new("b").bold().link("x"),
+                ColoredStr::new("c").bold().link("x"),
+            ]
+        );
+        assert_eq!(text.display_width(), 3);
+        assert_eq!(AsAnsi::get_data(&text), "abc");
+        assert_eq!(AsAnsi::display_width(&text), 3);
+    }
+}
@@ -0,0 +1,511 @@
+//! Measures how many terminal columns text takes up.
+//!
+//! Widths follow the East Asian Width property of Unicode, where wide and
+//! fullwidth characters take two columns, along with emoji shown in emoji
+//! presentation. Combining marks, joiners and other format characters take
+//! none. Text is measured by grapheme clusters, following a simplified form
+//! of the Unicode segmentation rules, so combined characters are counted once.
+//! Escape sequences take up no columns.
+
+use super::ansi::{Token, Tokens};
+
+/// Ranges of characters that take no columns of their own: combining marks,
+/// format characters, variation selectors and the Hangul vowels and trailing
+/// consonants that join a leading consonant.
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x0300, 0x036f),
+    (0x0483, 0x0489),
+    (0x0591, 0x05bd),
+    (0x05bf, 0x05bf),
+    (0x05c1, 0x05c2),
+    (0x05c4, 0x05c5),
+    (0x05c7, 0x05c7),
+    (0x0610, 0x061a),
+    (0x061c, 0x061c),
+    (0x064b, 0x065f),
+    (0x0670, 0x0670),
+    (0x06d6, 0x06dc),
+    (0x06df, 0x06e4),
+    (0x06e7, 0x06e8),
+    (0x06ea, 0x06ed),
+    (0x0711, 0x0711),
+    (0x0730, 0x074a),
+    (0x07a6, 0x07b0),
+    (0x07eb, 0x07f3),
+    (0x0816, 0x0819),
+    (0x081b, 0x0823),
+    (0x0825, 0x0827),
+    (0x0829, 0x082d),
+    (0x0859, 0x085b),
+    (0x08d3, 0x08e1),
+    (0x08e3, 0x0902),
+    (0x093a, 0x093a),
+    (0x093c, 0x093c),
+    (0x0941, 0x0948),
+    (0x094d, 0x094d),
+    (0x0951, 0x0957),
+    (0x0962, 0x0963),
+    (0x0981, 0x0981),
+    (0x09bc, 0x09bc),
+    (0x09c1, 0x09c4),
+    (0x09cd, 0x09cd),
+    (0x09e2, 0x09e3),
+    (0x0a01, 0x0a02),
+    (0x0a3c, 0x0a3c),
+    (0x0a41, 0x0a42),
+    (0x0a47, 0x0a48),
+    (0x0a4b, 0x0a4d),
+    (0x0a70, 0x0a71),
+    (0x0a81, 0x0a82),
+    (0x0abc, 0x0abc),
+    (0x0ac1, 0x0ac5),
+    (0x0ac7, 0x0ac8),
+    (0x0acd, 0x0acd),
+    (0x0b01, 0x0b01),
+    (0x0b3c, 0x0b3c),
+    (0x0b3f, 0x0b3f),
+    (0x0b41, 0x0b44),
+    (0x0b4d, 0x0b4d),
+    (0x0bc0, 0x0bc0),
+    (0x0bcd, 0x0bcd),
+    (0x0c3e, 0x0c40),
+    (0x0c46, 0x0c48),
+    (0x0c4a, 0x0c4d),
+    (0x0cbc, 0x0cbc),
+    (0x0ccc, 0x0ccd),
+    (0x0d41, 0x0d44),
+    (0x0d4d, 0x0d4d),
+    (0x0dca, 0x0dca),
+    (0x0dd2, 0x0dd4),
+    (0x0e31, 0x0e31),
+    (0x0e34, 0x0e3a),
+    (0x0e47, 0x0e4e),
+    (0x0eb1, 0x0eb1),
+    (0x0eb4, 0x0ebc),
+    (0x0ec8, 0x0ecd),
+    (0x0f18, 0x0f19),
+    (0x0f35, 0x0f35),
+    (0x0f37, 0x0f37),
+    (0x0f39, 0x0f39),
+    (0x0f71, 0x0f7e),
+    (0x0f80, 0x0f84),
+    (0x0f86, 0x0f87),
+    (0x0f8d, 0x0fbc),
+    (0x0fc6, 0x0fc6),
+    (0x102d, 0x1030),
+    (0x1032, 0x1037),
+    (0x1039, 0x103a),
+    (0x103d, 0x103e),
+    (0x1058, 0x1059),
+    (0x105e, 0x1060),
+    (0x1071, 0x1074),
+    (0x1082, 0x1082),
+    (0x1085, 0x1086),
+    (0x108d, 0x108d),
+    (0x109d, 0x109d),
+    (0x1160, 0x11ff),
+    (0x135d, 0x135f),
+    (0x1712, 0x1714),
+    (0x1732, 0x1734),
+    (0x1752, 0x1753),
+    (0x1772, 0x1773),
+    (0x17b4, 0x17b5),
+    (0x17b7, 0x17bd),
+    (0x17c6, 0x17c6),
+    (0x17c9, 0x17d3),
+    (0x17dd, 0x17dd),
+    (0x180b, 0x180f),
+    (0x18a9, 0x18a9),
+    (0x1920, 0x1922),
+    (0x1927, 0x1928),
+    (0x1932, 0x1932),
+    (0x1939, 0x193b),
+    (0x1a17, 0x1a18),
+    (0x1ab0, 0x1aff),
+    (0x1b00, 0x1b03),
+    (0x1b34, 0x1b34),
+    (0x1b36, 0x1b3a),
+    (0x1b3c, 0x1b3c),
+    (0x1b42, 0x1b42),
+    (0x1b6b, 0x1b73),
+    (0x1dc0, 0x1dff),
+    (0x200b, 0x200f),
+    (0x2028, 0x202e),
+    (0x2060, 0x2064),
+    (0x20d0, 0x20f0),
+    (0x2cef, 0x2cf1),
+    (0x2d7f, 0x2d7f),
+    (0x2de0, 0x2dff),
+    (0x302a, 0x302d),
+    (0x3099, 0x309a),
+    (0xa66f, 0xa672),
+    (0xa674, 0xa67d),
+    (0xa69e, 0xa69f),
+    (0xa6f0, 0xa6f1),
+    (0xa802, 0xa802),
+    (0xa806, 0xa806),
+    (0xa80b, 0xa80b),
+    (0xa825, 0xa826),
+    (0xa8c4, 0xa8c5),
+    (0xa8e0, 0xa8f1),
+    (0xa926, 0xa92d),
+    (0xa947, 0xa951),
+    (0xa980, 0xa982),
+    (0xa9b3, 0xa9b3),
+    (0xa9b6, 0xa9b9),
+    (0xa9bc, 0xa9bc),
+    (0xaa29, 0xaa2e),
+    (0xaa31, 0xaa32),
+    (0xaa35, 0xaa36),
+    (0xaa43, 0xaa43),
+    (0xaa4c, 0xaa4c),
+    (0xaab0, 0xaab0),
+    (0xaab2, 0xaab4),
+    (0xaab7, 0xaab8),
+    (0xaabe, 0xaabf),
+    (0xaac1, 0xaac1),
+    (0xaaec, 0xaaed),
+    (0xaaf6, 0xaaf6),
+    (0xabe5, 0xabe5),
+    (0xabe8, 0xabe8),
+    (0xabed, 0xabed),
+    (0xd7b0, 0xd7ff),
+    (0xfb1e, 0xfb1e),
+    (0xfe00, 0xfe0f),
+    (0xfe20, 0xfe2f),
+    (0xfeff, 0xfeff),
+    (0xfff9, 0xfffb),
+    (0x101fd, 0x101fd),
+    (0x1d167, 0x1d169),
+    (0x1d17b, 0x1d182),
+    (0x1d185, 0x1d18b),
+    (0x1d1aa, 0x1d1ad),
+    (0xe0001, 0xe0001),
+    (0xe0020, 0xe007f),
+    (0xe0100, 0xe01ef),
+];
+
+/// Ranges of East Asian wide and fullwidth characters, and of emoji that are
+/// shown in emoji presentation by default.
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115f),
+    (0x231a, 0x231b),
+    (0x2329, 0x232a),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x2e80, 0x2e99),
+    (0x2e9b, 0x2ef3),
+    (0x2f00, 0x2fd5),
+    (0x2ff0, 0x2ffb),
+    (0x3000, 0x303e),
+    (0x3041, 0x3096),
+    (0x309b, 0x30ff),
+    (0x3105, 0x312f),
+    (0x3131, 0x318e),
+    (0x3190, 0x31e3),
+    (0x31f0, 0x321e),
+    (0x3220, 0x3247),
+    (0x3250, 0x4dbf),
+    (0x4e00, 0xa48c),
+    (0xa490, 0xa4c6),
+    (0xa960, 0xa97c),
+    (0xac00, 0xd7a3),
+    (0xf900, 0xfaff),
+    (0xfe10, 0xfe19),
+    (0xfe30, 0xfe52),
+    (0xfe54, 0xfe66),
+    (0xfe68, 0xfe6b),
+    (0xff01, 0xff60),
+    (0xffe0, 0xffe6),
+    (0x16fe0, 0x16fe4),
+    (0x17000, 0x187f7),
+    (0x18800, 0x18cd5),
+    (0x1b000, 0x1b122),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f200, 0x1f202),
+    (0x1f210, 0x1f23b),
+    (0x1f240, 0x1f248),
+    (0x1f250, 0x1f251),
+    (0x1f260, 0x1f265),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d7),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1faff),
+    (0x20000, 0x2fffd),
+    (0x30000, 0x3fffd),
+];
+
+const ZWJ: char = '\u{200d}';
+const EMOJI_PRESENTATION: char = '\u{fe0f}';
+
+fn in_table(table: &[(u32, u32)], c: char) -> bool {
+    let c = c as u32;
+    table
+        .binary_search_by(|&(lo, hi)| {
+            if hi < c {
+                std::cmp::Ordering::Less
+            } else if lo > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+fn is_emoji_modifier(c: char) -> bool {
+    ('\u{1f3fb}'..='\u{1f3ff}').contains(&c)
+}
+
+/// Counts the columns a single character takes up on its own.
+pub(crate) fn char_width(c: char) -> usize {
+    if c.is_control() || in_table(ZERO_WIDTH, c) {
+        0
+    } else if in_table(WIDE, c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// Checks if `c` continues the grapheme cluster before it.
+fn extends(c: char) -> bool {
+    c == ZWJ || is_emoji_modifier(c) || !c.is_control() && in_table(ZERO_WIDTH, c)
+}
+
+/// Iterates over the grapheme clusters of a string.
+#[derive(Debug, Clone)]
+pub(crate) struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+pub(crate) fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let mut end = first.len_utf8();
+        let mut prev = first;
+        for (i, c) in chars {
+            let joins = match (prev, c) {
+                ('\r', '\n') => true,
+                (ZWJ, _) => true,
+                _ if prev.is_control() => false,
+                // flags are made of pairs of regional indicators
+                _ if is_regional_indicator(c) => {
+                    is_regional_indicator(first) && end == first.len_utf8()
+                }
+                _ => extends(c),
+            };
+            if !joins {
+                break;
+            }
+            end = i + c.len_utf8();
+            prev = c;
+        }
+        let (grapheme, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(grapheme)
+    }
+}
+
+/// Counts the columns a grapheme cluster takes up.
+pub(crate) fn grapheme_width(grapheme: &str) -> usize {
+    let mut chars = grapheme.chars();
+    let Some(first) = chars.next() else {
+        return 0;
+    };
+    match char_width(first) {
+        0 => 0,
+        1 if is_regional_indicator(first) || chars.any(|c| c == EMOJI_PRESENTATION) => 2,
+        width => width,
+    }
+}
+
+/// Counts the columns the text takes up, ignoring any escape sequences.
+pub(crate) fn str_width(s: &str) -> usize {
+    Tokens::new(s)
+        .map(|token| match token {
+            Token::Text(text) => graphemes(text).map(grapheme_width).sum(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Cuts the text down to at most `columns` columns without splitting a
+/// grapheme cluster or an escape sequence, returning what is kept along with
+/// its width.
+///
+/// Escape sequences take up no columns, so those directly after the last
+/// grapheme cluster that fits are kept.
+pub(crate) fn truncate(s: &str, columns: usize) -> (&str, usize) {
+    let mut end = 0;
+    let mut width = 0;
+    let mut tokens = Tokens::new(s);
+    while let Some(token) = tokens.next() {
+        if let Token::Text(text) = token {
+            for grapheme in graphemes(text) {
+                let next = width + grapheme_width(grapheme);
+                if next > columns {
+                    return (&s[..end], width);
+                }
+                end += grapheme.len();
+                width = next;
+            }
+        }
+        end = s.len() - tokens.as_str().len();
+    }
+    (&s[..end], width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ut_tables_sorted() {
+        for table in [ZERO_WIDTH, WIDE] {
+            assert!(table.iter().all(|(lo, hi)| lo <= hi));
+            assert!(table.windows(2).all(|w| w[0].1 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn ut_char_width() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('\u{e9}'), 1);
+        assert_eq!(char_width('\u{4e2d}'), 2);
+        assert_eq!(char_width('\u{ff21}'), 2);
+        assert_eq!(char_width('\u{1f600}'), 2);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(char_width('\u{200b}'), 0);
+        assert_eq!(char_width('\t'), 0);
+    }
+
+    #[test]
+    fn ut_graphemes() {
+        let split = |s| graphemes(s).collect::<Vec<&str>>();
+        assert_eq!(split("ab"), vec!["a", "b"]);
+        assert_eq!(split("e\u{301}x"), vec!["e\u{301}", "x"]);
+        assert_eq!(split("\r\n\n"), vec!["\r\n", "\n"]);
+        // a family joined with zero width joiners and a waving hand with a skin tone
+        assert_eq!(
+            split("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}\u{1f44b}\u{1f3fd}"),
+            vec![
+                "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}",
+                "\u{1f44b}\u{1f3fd}"
+            ]
+        );
+        // flags pair up regional indicators
+        assert_eq!(
+            split("\u{1f1ef}\u{1f1f5}\u{1f1fa}\u{1f1f8}\u{1f1eb}"),
+            vec!["\u{1f1ef}\u{1f1f5}", "\u{1f1fa}\u{1f1f8}", "\u{1f1eb}"]
+        );
+        // Hangul syllables built from separate jamo
+        assert_eq!(
+            split("\u{1100}\u{1161}\u{11a8}"),
+            vec!["\u{1100}\u{1161}\u{11a8}"]
+        );
+        assert_eq!(split(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn ut_truncate() {
+        assert_eq!(truncate("hello", 3), ("hel", 3));
+        assert_eq!(truncate("hello", 10), ("hello", 5));
+        // a wide character that does not fit is left out entirely
+        assert_eq!(truncate("a\u{4e2d}b", 2), ("a", 1));
+        assert_eq!(truncate("e\u{301}e\u{301}", 1), ("e\u{301}", 1));
+        assert_eq!(truncate("abc", 0), ("", 0));
+    }
+
+    #[test]
+    fn ut_escapes() {
+        assert_eq!(str_width("\u{1b}[31mred\u{1b}[39m"), 3);
+        // escapes are never cut and those right after the cut are kept
+        assert_eq!(
+            truncate("\u{1b}[31mred\u{1b}[39m", 3),
+            ("\u{1b}[31mred\u{1b}[39m", 3)
+        );
+        assert_eq!(truncate("\u{1b}[31mred\u{1b}[39m", 2), ("\u{1b}[31mre", 2));
+        assert_eq!(truncate("\u{1b}[31mred", 0), ("\u{1b}[31m", 0));
+    }
+
+    #[test]
+    fn ut_str_width() {
+        assert_eq!(str_width("hello"), 5);
+        assert_eq!(str_width("\u{65e5}\u{672c}\u{8a9e}"), 6);
+        assert_eq!(str_width("cafe\u{301}"), 4);
+        assert_eq!(str_width("\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}!"), 3);
+        assert_eq!(str_width("\u{2764}\u{fe0f}"), 2);
+        assert_eq!(str_width("\u{1f1ef}\u{1f1f5}"), 2);
+        assert_eq!(str_width(""), 0);
+    }
+}
@@ -0,0 +1,316 @@
+use super::{parse, width, StyledText};
+
+/// Settings for [wrap] that decide how wide lines get and how they start.
+///
+/// ```
+/// use crayon::WrapOptions;
+///
+/// // a hanging indent lines up wrapped lines under the first word
+/// const HELP: WrapOptions = WrapOptions::new(40)
+///     .initial_indent("  -v  ")
+///     .subsequent_indent("      ");
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WrapOptions<'a> {
+    width: usize,
+    initial_indent: &'a str,
+    subsequent_indent: &'a str,
+}
+
+impl<'a> WrapOptions<'a> {
+    /// Wraps lines to at most `width` terminal columns, including indents.
+    pub const fn new(width: usize) -> Self {
+        Self {
+            width,
+            initial_indent: "",
+            subsequent_indent: "",
+        }
+    }
+
+    /// Places `indent` at the start of the first line.
+    pub const fn initial_indent(mut self, indent: &'a str) -> Self {
+        self.initial_indent = indent;
+        self
+    }
+
+    /// Places `indent` at the start of every line after the first.
+    pub const fn subsequent_indent(mut self, indent: &'a str) -> Self {
+        self.subsequent_indent = indent;
+        self
+    }
+}
+
+/// Wraps text into lines of at most the width of `options` in terminal
+/// columns, breaking at whitespace between words.
+///
+/// Each line keeps the codes of the spans it is made of, so styles carry over
+/// onto wrapped lines. Line breaks in the text are kept, a word that does not
+/// fit moves onto the next line, words wider than a whole line after its indent
+/// are broken between grapheme clusters, and whitespace where a line is wrapped
+/// is dropped. Escape sequences embedded in the text are parsed as by
+/// [parse_ansi](crate::parse_ansi), so text formatted from colored strings keeps
+/// its styles on every line.
+///
+/// ```
+/// use crayon::{wrap, Color, WrapOptions};
+///
+/// let text = "error:".red().bold() + " the file could not be found";
+/// let lines = wrap(text, &WrapOptions::new(16).subsequent_indent("  "));
+/// let lines: Vec<&str> = lines.iter().map(|line| line.get_data()).collect();
+/// assert_eq!(lines, vec!["error: the file", "  could not be", "  found"]);
+/// ```
+pub fn wrap<T: Into<StyledText>>(text: T, options: &WrapOptions<'_>) -> Vec<StyledText> {
+    let text = parse::parse_nested(&text.into());
+    let mut wrapper = Wrapper {
+        text: &text,
+        options,
+        lines: Vec::new(),
+        start: 0,
+        end: 0,
+        width: width::str_width(options.initial_indent),
+    };
+    let mut base = 0;
+    for line in text.get_data().split('\n') {
+        wrapper.start = base;
+        wrapper.end = base;
+        for (word_start, word_end) in words(line) {
+            wrapper.place(base + word_start, base + word_end);
+        }
+        wrapper.break_line();
+        base += line.len() + 1;
+    }
+    wrapper.lines
+}
+
+/// Finds the byte ranges of the runs of non-whitespace characters in `line`.
+fn words(line: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, line.len()));
+    }
+    words
+}
+
+/// Builds up wrapped lines from byte ranges of the text.
+struct Wrapper<'t, 'o> {
+    text: &'t StyledText,
+    options: &'t WrapOptions<'o>,
+    lines: Vec<StyledText>,
+    /// Byte offset where the contents of the current line start.
+    start: usize,
+    /// Byte offset where the contents of the current line end.
+    end: usize,
+    /// Columns the current line takes up so far, including its indent.
+    width: usize,
+}
+
+impl Wrapper<'_, '_> {
+    fn indent(&self) -> &str {
+        match self.lines.is_empty() {
+            true => self.options.initial_indent,
+            false => self.options.subsequent_indent,
+        }
+    }
+
+    /// Ends the current line and starts the next one at its end.
+    fn break_line(&mut self) {
+        let line = StyledText::from(self.indent()) + self.text.slice(self.start, self.end);
+        self.lines.push(line);
+        self.start = self.end;
+        self.width = width::str_width(self.indent());
+    }
+
+    /// Places the word from `start` to `end` on the current line, or moves it
+    /// onto the next line when it does not fit.
+    ///
+    /// Only words wider than a whole line after its indent are broken up.
+    fn place(&mut self, start: usize, end: usize) {
+        let data = self.text.get_data();
+        let mut gap = width::str_width(&data[self.end..start]);
+        let word = width::str_width(&data[start..end]);
+        // nothing is on the line yet but its indent, not even whitespace
+        let is_blank = self.start == self.end && gap == 0;
+        let fits_next = word
+            <= self
+                .options
+                .width
+                .saturating_sub(width::str_width(self.options.subsequent_indent));
+        if self.width + gap + word > self.options.width && (!is_blank || fits_next) {
+            self.break_line();
+            gap = 0;
+        }
+        if self.start == self.end && gap == 0 {
+            self.start = start;
+            self.end = start;
+        }
+        self.width += gap;
+        // break words that are wider than a whole line
+        let mut rest = &data[start..end];
+        loop {
+            let available = self.options.width.saturating_sub(self.width);
+            let (mut piece, _) = width::truncate(rest, available);
+            if piece.is_empty() {
+                // a grapheme cluster wider than the line still has to go somewhere
+                piece = width::graphemes(rest).next().unwrap_or(rest);
+            }
+            self.width += width::str_width(piece);
+            rest = &rest[piece.len()..];
+            self.end = end - rest.len();
+            if rest.is_empty() {
+                break;
+            }
+            self.break_line();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{override_color_level, with_color, Color, ColorLevel, ColoredStr};
+
+    fn plain(lines: &[StyledText]) -> Vec<&str> {
+        lines.iter().map(|line| line.get_data()).collect()
+    }
+
+    #[test]
+    fn ut_wrap_words() {
+        let options = WrapOptions::new(10);
+        assert_eq!(
+            plain(&wrap("the quick brown fox jumps", &options)),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        // line breaks and leading whitespace are kept
+        assert_eq!(
+            plain(&wrap("a b\n\n  c   d  ", &options)),
+            vec!["a b", "", "  c   d"]
+        );
+        // long words are broken across lines
+        assert_eq!(
+            plain(&wrap("see abcdefghijklmnopqrstuvwxyz", &options)),
+            vec!["see", "abcdefghij", "klmnopqrst", "uvwxyz"]
+        );
+        // by display width rather than bytes
+        assert_eq!(
+            plain(&wrap(
+                "\u{65e5}\u{672c}\u{8a9e} \u{65e5}\u{672c}",
+                &WrapOptions::new(6)
+            )),
+            vec!["\u{65e5}\u{672c}\u{8a9e}", "\u{65e5}\u{672c}"]
+        );
+        assert_eq!(plain(&wrap("", &options)), vec![""]);
+    }
+
+    #[test]
+    fn ut_wrap_leading_whitespace() {
+        // words after leading whitespace move down instead of being broken
+        assert_eq!(
+            plain(&wrap("  abcd", &WrapOptions::new(5))),
+            vec!["", "abcd"]
+        );
+        assert_eq!(
+            plain(&wrap("  ab cd", &WrapOptions::new(5))),
+            vec!["  ab", "cd"]
+        );
+        assert_eq!(
+            plain(&wrap("x\n    abcd efgh", &WrapOptions::new(6))),
+            vec!["x", "", "abcd", "efgh"]
+        );
+        // unless they are wider than a whole line
+        assert_eq!(
+            plain(&wrap("  abcdefg", &WrapOptions::new(5))),
+            vec!["", "abcde", "fg"]
+        );
+    }
+
+    #[test]
+    fn ut_wrap_indents() {
+        let options = WrapOptions::new(12)
+            .initial_indent("* ")
+            .subsequent_indent("  ");
+        assert_eq!(
+            plain(&wrap("one two three four five", &options)),
+            vec!["* one two", "  three four", "  five"]
+        );
+        // continuation lines are not broken after their indent
+        let options = WrapOptions::new(14)
+            .initial_indent("  -v  ")
+            .subsequent_indent("      ");
+        assert_eq!(
+            plain(&wrap("print verbose output", &options)),
+            vec!["  -v  print", "      verbose", "      output"]
+        );
+        let options = WrapOptions::new(6).subsequent_indent("  ");
+        assert_eq!(
+            plain(&wrap("ab abcdefgh", &options)),
+            vec!["ab", "  abcd", "  efgh"]
+        );
+        // a word moves off a first line whose indent leaves it too little room
+        let options = WrapOptions::new(5).initial_indent("* ");
+        assert_eq!(plain(&wrap("abcd ef", &options)), vec!["* ", "abcd", "ef"]);
+        // indents wider than the line still make progress
+        let options = WrapOptions::new(2).subsequent_indent("    ");
+        assert_eq!(
+            plain(&wrap("ab cd", &options)),
+            vec!["ab", "    c", "    d"]
+        );
+    }
+
+    #[test]
+    fn ut_wrap_styles() {
+        let _level = override_color_level(ColorLevel::TrueColor);
+        let text = "warning:".yellow() + " unused " + "variable name".bold() + " here";
+        let lines = wrap(text, &WrapOptions::new(12).subsequent_indent("  "));
+        assert_eq!(
+            lines[3].spans().collect::<Vec<ColoredStr>>(),
+            vec![
+                ColoredStr::new("  "),
+                ColoredStr::new("name").bold(),
+                ColoredStr::new(" here"),
+            ]
+        );
+        // each line starts with the codes active at its start
+        with_color(true, || {
+            assert_eq!(
+                lines
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect::<Vec<String>>(),
+                vec![
+                    "\u{1b}[33mwarning:\u{1b}[39m",
+                    "  unused",
+                    "  \u{1b}[1mvariable\u{1b}[22m",
+                    "  \u{1b}[1mname\u{1b}[22m here",
+                ]
+            );
+            // text already formatted with codes keeps them on wrapped lines
+            let lines = wrap(
+                format!("{} end", "one two three".red()),
+                &WrapOptions::new(8),
+            );
+            assert_eq!(
+                lines
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect::<Vec<String>>(),
+                vec![
+                    "\u{1b}[31mone two\u{1b}[39m",
+                    "\u{1b}[31mthree\u{1b}[39m",
+                    "end"
+                ]
+            );
+            assert_eq!(lines[1], StyledText::from("three".red()));
+        });
+    }
+}